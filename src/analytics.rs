@@ -0,0 +1,198 @@
+use chrono::{DateTime, Utc};
+use tracing::instrument;
+
+use crate::{
+    client::YandexWebmasterClient,
+    dto::{
+        ApiDeviceTypeIndicator, ApiQueryIndicator, QueryAnalyticsRequest, QueryAnalyticsResponse,
+        QueryHistoryRequest, QueryHistoryResponse,
+    },
+    error::Result,
+};
+
+/// Fluent builder of search query analytics requests
+///
+/// Created with [`YandexWebmasterClient::search_analytics`]. Composes a
+/// [`QueryAnalyticsRequest`] and sends it with [`Self::run`], or sends the same
+/// parameters for a single query with [`Self::run_for_query`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use yandex_webmaster_api::*;
+/// # async fn example(client: YandexWebmasterClient) -> Result<()> {
+/// let analytics = client
+///     .search_analytics("https:example.com:443")
+///     .indicators([ApiQueryIndicator::TotalShows, ApiQueryIndicator::TotalClicks])
+///     .device(ApiDeviceTypeIndicator::Mobile)
+///     .last_days(90)
+///     .run()
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SearchAnalytics {
+    client: YandexWebmasterClient,
+    host_id: String,
+    request: QueryAnalyticsRequest,
+}
+
+impl SearchAnalytics {
+    /// Creates a builder without indicators for the whole available date range
+    pub(crate) fn new(client: YandexWebmasterClient, host_id: String) -> Self {
+        Self {
+            client,
+            host_id,
+            request: QueryAnalyticsRequest {
+                query_indicator: Vec::new(),
+                device_type_indicator: None,
+                date_from: None,
+                date_to: None,
+            },
+        }
+    }
+
+    /// Adds the indicators to the requested ones
+    pub fn indicators(mut self, indicators: impl IntoIterator<Item = ApiQueryIndicator>) -> Self {
+        for indicator in indicators {
+            if !self.request.query_indicator.contains(&indicator) {
+                self.request.query_indicator.push(indicator);
+            }
+        }
+        self
+    }
+
+    /// Limits the statistics to the device type (default: all devices)
+    pub fn device(mut self, device: ApiDeviceTypeIndicator) -> Self {
+        self.request.device_type_indicator = Some(device);
+        self
+    }
+
+    /// Sets the date range
+    pub fn date_range(mut self, date_from: DateTime<Utc>, date_to: DateTime<Utc>) -> Self {
+        self.request.date_from = Some(date_from);
+        self.request.date_to = Some(date_to);
+        self
+    }
+
+    /// Sets the date range to the last `days` days up to now
+    pub fn last_days(mut self, days: i64) -> Self {
+        self.request = self.request.with_last_days(days);
+        self
+    }
+
+    /// Returns the composed request
+    pub fn request(&self) -> &QueryAnalyticsRequest {
+        &self.request
+    }
+
+    /// Fetches the history of the indicators for all queries of the host
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::YandexWebmasterError::InvalidRequest`] if no indicators were set.
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn run(&self) -> Result<QueryAnalyticsResponse> {
+        self.client
+            .get_query_analytics(&self.host_id, &self.request)
+            .await
+    }
+
+    /// Fetches the history of the indicators for a single query
+    ///
+    /// The date range is truncated to whole days since the endpoint accepts dates only.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::YandexWebmasterError::InvalidRequest`] if no indicators were set.
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn run_for_query(&self, query_id: &str) -> Result<QueryHistoryResponse> {
+        let request = QueryHistoryRequest {
+            query_indicator: self.request.query_indicator.clone(),
+            device_type_indicator: self.request.device_type_indicator,
+            date_from: self.request.date_from.map(|date| date.date_naive()),
+            date_to: self.request.date_to.map(|date| date.date_naive()),
+        };
+        self.client
+            .get_query_history(&self.host_id, query_id, &request)
+            .await
+    }
+}
+
+impl YandexWebmasterClient {
+    /// Starts a [fluent analytics request](SearchAnalytics) for the host
+    pub fn search_analytics(&self, host_id: &str) -> SearchAnalytics {
+        SearchAnalytics::new(self.clone(), host_id.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        error::YandexWebmasterError,
+        test_util::{MOCK_USER_ID, SAMPLE_HOST_ID},
+    };
+    use wiremock::{
+        matchers::{method, path, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_search_analytics() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/user/{}/hosts/{}/search-queries/all/history",
+                MOCK_USER_ID, SAMPLE_HOST_ID
+            )))
+            .and(query_param("query_indicator", "TOTAL_SHOWS"))
+            .and(query_param("device_type_indicator", "MOBILE"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "indicators": {
+                    "TOTAL_SHOWS": [{ "date": "2024-01-01T00:00:00.000+0300", "value": 100.0 }]
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/user/{}/hosts/{}/search-queries/c3f9a0b5/history",
+                MOCK_USER_ID, SAMPLE_HOST_ID
+            )))
+            .and(query_param("device_type_indicator", "MOBILE"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "query_id": "c3f9a0b5",
+                "query_text": "yandex webmaster",
+                "indicators": {
+                    "TOTAL_SHOWS": [{ "date": "2024-01-01T00:00:00.000+0300", "value": 10.0 }]
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let analytics = client
+            .search_analytics(SAMPLE_HOST_ID)
+            .indicators([ApiQueryIndicator::TotalShows, ApiQueryIndicator::TotalShows])
+            .device(ApiDeviceTypeIndicator::Mobile)
+            .last_days(90);
+        assert_eq!(analytics.request().query_indicator.len(), 1);
+        assert!(analytics.request().date_from.is_some());
+
+        let response = analytics.run().await.unwrap();
+        assert_eq!(response.indicators[&ApiQueryIndicator::TotalShows].len(), 1);
+
+        let response = analytics.run_for_query("c3f9a0b5").await.unwrap();
+        assert_eq!(response.query_id, "c3f9a0b5");
+
+        let result = client.search_analytics(SAMPLE_HOST_ID).run().await;
+        assert!(matches!(
+            result,
+            Err(YandexWebmasterError::InvalidRequest(_))
+        ));
+    }
+}
@@ -0,0 +1,396 @@
+use reqwest_middleware::ClientBuilder;
+use std::{future::Future, sync::Arc, time::Duration};
+
+use crate::{
+    cache::ResponseCache,
+    client::{ApiRegion, YandexWebmasterClient, INDEX_NOW_URL},
+    error::Result,
+    middleware::TokenRefresher,
+};
+
+/// Default maximum number of idle connections per host
+const DEFAULT_POOL_MAX_IDLE_PER_HOST: usize = 8;
+
+/// Default timeout for idle connections
+const DEFAULT_POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Default minimum size of a request body to compress
+const DEFAULT_COMPRESSION_THRESHOLD: usize = 8 * 1024;
+
+/// Default time-to-live of cached responses
+const DEFAULT_RESPONSE_CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+
+/// Default number of retries of GET requests failed to connect
+const DEFAULT_CONNECTION_RETRIES: u32 = 1;
+
+/// Default TCP keepalive interval
+const DEFAULT_TCP_KEEPALIVE: Duration = Duration::from_secs(60);
+
+/// Builder for [`YandexWebmasterClient`]
+///
+/// Created with [`YandexWebmasterClient::builder`]. All options are optional and
+/// default to the behavior of [`YandexWebmasterClient::new`].
+pub struct YandexWebmasterClientBuilder {
+    pub(crate) oauth_token: String,
+    pub(crate) client: Option<ClientBuilder>,
+    pub(crate) base_url: String,
+    pub(crate) language: Option<String>,
+    pub(crate) treat_already_added_as_success: bool,
+    pub(crate) host_cache_ttl: Option<Duration>,
+    pub(crate) pool_max_idle_per_host: usize,
+    pub(crate) pool_idle_timeout: Option<Duration>,
+    pub(crate) tcp_keepalive: Option<Duration>,
+    pub(crate) compress_requests: bool,
+    pub(crate) compression_threshold: usize,
+    pub(crate) max_body_size: Option<usize>,
+    pub(crate) request_id: bool,
+    pub(crate) strict_deserialization: bool,
+    pub(crate) max_concurrent_requests: Option<usize>,
+    pub(crate) token_refresher: Option<TokenRefresher>,
+    pub(crate) slow_request_threshold: Option<Duration>,
+    pub(crate) response_cache: Option<Arc<dyn ResponseCache>>,
+    pub(crate) response_cache_ttl: Duration,
+    pub(crate) endpoint_cache_ttls: Vec<(String, Duration)>,
+    pub(crate) log_user_id: bool,
+    pub(crate) parent_span: Option<tracing::Span>,
+    pub(crate) connection_retries: u32,
+    pub(crate) index_now_key: Option<String>,
+    pub(crate) index_now_key_location: Option<String>,
+    pub(crate) index_now_url: String,
+}
+
+impl YandexWebmasterClientBuilder {
+    /// Creates a new builder with the provided OAuth token
+    pub(crate) fn new(oauth_token: String) -> Self {
+        Self {
+            oauth_token,
+            client: None,
+            base_url: ApiRegion::default().base_url().to_string(),
+            language: None,
+            treat_already_added_as_success: false,
+            host_cache_ttl: None,
+            pool_max_idle_per_host: DEFAULT_POOL_MAX_IDLE_PER_HOST,
+            pool_idle_timeout: Some(DEFAULT_POOL_IDLE_TIMEOUT),
+            tcp_keepalive: Some(DEFAULT_TCP_KEEPALIVE),
+            compress_requests: false,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+            max_body_size: None,
+            request_id: false,
+            strict_deserialization: false,
+            max_concurrent_requests: None,
+            token_refresher: None,
+            slow_request_threshold: None,
+            response_cache: None,
+            response_cache_ttl: DEFAULT_RESPONSE_CACHE_TTL,
+            endpoint_cache_ttls: Vec::new(),
+            log_user_id: true,
+            parent_span: None,
+            connection_retries: DEFAULT_CONNECTION_RETRIES,
+            index_now_key: None,
+            index_now_key_location: None,
+            index_now_url: INDEX_NOW_URL.to_string(),
+        }
+    }
+
+    /// Uses a client builder with preconfigured middleware instead of a default one
+    ///
+    /// Connection pool settings of this builder are ignored in that case.
+    pub fn client(mut self, client: ClientBuilder) -> Self {
+        self.client = Some(client);
+        self
+    }
+
+    /// Overrides the base URL of the API (e.g. to point the client at a mock server)
+    ///
+    /// Default: `https://api.webmaster.yandex.net/v4`.
+    pub fn base_url(mut self, base_url: String) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Sends requests to the endpoint of the region
+    ///
+    /// Replaces the [base URL](Self::base_url). Default: [`ApiRegion::Global`].
+    pub fn region(mut self, region: ApiRegion) -> Self {
+        self.base_url = region.base_url().to_string();
+        self
+    }
+
+    /// Sets the `Accept-Language` header sent with every request (e.g. `"en"`)
+    ///
+    /// Affects the language of `error_message` texts returned by the API.
+    /// By default the header is not sent and the account's language is used.
+    pub fn language(mut self, language: String) -> Self {
+        self.language = Some(language);
+        self
+    }
+
+    /// Treats `HOST_ALREADY_ADDED` and `SITEMAP_ALREADY_ADDED` errors as success
+    ///
+    /// When enabled, `add_host` and `add_sitemap` return the ID of the existing
+    /// resource from the error body, which makes them safe to retry. Default: `false`.
+    pub fn treat_already_added_as_success(mut self, value: bool) -> Self {
+        self.treat_already_added_as_success = value;
+        self
+    }
+
+    /// Enables the in-memory cache of `get_host_cached` results with the provided TTL
+    ///
+    /// Cached entries are refreshed by `get_host` and removed by `delete_host`.
+    /// Disabled by default.
+    pub fn host_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.host_cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Sets the maximum number of idle connections kept per host
+    ///
+    /// Default: 8, which covers typical concurrent usage within the API rate limits.
+    pub fn pool_max_idle_per_host(mut self, max: usize) -> Self {
+        self.pool_max_idle_per_host = max;
+        self
+    }
+
+    /// Sets the timeout for idle connections in the pool, `None` keeps them forever
+    ///
+    /// Default: 90 seconds.
+    pub fn pool_idle_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.pool_idle_timeout = timeout;
+        self
+    }
+
+    /// Sets the TCP keepalive interval, `None` disables it
+    ///
+    /// Default: 60 seconds.
+    pub fn tcp_keepalive(mut self, interval: Option<Duration>) -> Self {
+        self.tcp_keepalive = interval;
+        self
+    }
+
+    /// Compresses JSON bodies of POST requests with gzip
+    ///
+    /// Only bodies larger than the [compression threshold](Self::compression_threshold)
+    /// are compressed. If the API rejects a compressed body, the request is resent
+    /// uncompressed and compression is disabled for the client. Default: `false`.
+    pub fn compress_requests(mut self, value: bool) -> Self {
+        self.compress_requests = value;
+        self
+    }
+
+    /// Sets the minimum size in bytes of a request body to compress
+    ///
+    /// Default: 8 KiB.
+    pub fn compression_threshold(mut self, bytes: usize) -> Self {
+        self.compression_threshold = bytes;
+        self
+    }
+
+    /// Sets the maximum size in bytes of a request body
+    ///
+    /// Larger bodies fail with [`crate::YandexWebmasterError::PayloadTooLarge`] without
+    /// being sent, instead of being rejected by the API with `REQUEST_ENTITY_TOO_LARGE`.
+    /// The size is checked before [compression](Self::compress_requests). Methods sending
+    /// lists, such as [`YandexWebmasterClient::delete_feeds`], split them into requests
+    /// fitting the limit. Default: no limit.
+    pub fn max_body_size(mut self, bytes: usize) -> Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+
+    /// Sends a generated UUID in the `X-Request-Id` header of every request
+    ///
+    /// The ID of a failed request is returned by [`crate::YandexWebmasterError::request_id`].
+    /// Use [`YandexWebmasterClient::with_request_id`] to send an explicit ID instead.
+    /// Default: `false`.
+    pub fn request_id(mut self, value: bool) -> Self {
+        self.request_id = value;
+        self
+    }
+
+    /// Fails on response fields unknown to the crate's types
+    ///
+    /// Unknown fields are reported as [`crate::YandexWebmasterError::ResponseParseError`]
+    /// with the path of the first unexpected field, which helps to notice API changes.
+    /// Default: `false`, unknown fields are ignored.
+    pub fn strict_deserialization(mut self, value: bool) -> Self {
+        self.strict_deserialization = value;
+        self
+    }
+
+    /// Limits the number of requests in flight across all clones of the client
+    ///
+    /// Rate limits of the API apply to the whole account, so this smooths bursts of
+    /// concurrent calls for different hosts. A request holds its slot until the response
+    /// headers are received, including retries made by middleware passed to
+    /// [`Self::client`], so a retrying request doesn't let another one through.
+    /// A limit of `0` means no limit. Default: no limit.
+    pub fn max_concurrent_requests(mut self, max: usize) -> Self {
+        self.max_concurrent_requests = Some(max);
+        self
+    }
+
+    /// Sets a callback returning a new OAuth token when the current one is rejected
+    ///
+    /// A request failed with `INVALID_OAUTH_TOKEN` calls the callback and is retried once
+    /// with the new token, which is then used for all subsequent requests. Concurrent
+    /// failures share a single refresh. Requests with streaming bodies are not retried.
+    /// Default: no refresh.
+    pub fn token_refresher<F, Fut>(mut self, refresher: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String>> + Send + 'static,
+    {
+        self.token_refresher = Some(Arc::new(move || Box::pin(refresher())));
+        self
+    }
+
+    /// Logs a warning for requests taking longer than the threshold
+    ///
+    /// The warning carries the method, the URL path and the elapsed time, including
+    /// a retry after a [token refresh](Self::token_refresher). Default: disabled.
+    pub fn slow_request_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_request_threshold = Some(threshold);
+        self
+    }
+
+    /// Caches the bodies of successful GET responses in the store
+    ///
+    /// Entries live for the [default TTL](Self::response_cache_ttl) unless an
+    /// [endpoint TTL](Self::endpoint_cache_ttl) matches. Mutating requests are never
+    /// cached and invalidate the cached responses of their site; adding, deleting or
+    /// verifying a site invalidates the responses of all sites. `get_host` and the
+    /// `wait_for_*` polling methods always bypass the cache. Default: no cache.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use yandex_webmaster_api::{InMemoryResponseCache, YandexWebmasterClient};
+    ///
+    /// let builder = YandexWebmasterClient::builder("token".to_string())
+    ///     .response_cache(InMemoryResponseCache::new(1000))
+    ///     .endpoint_cache_ttl("/summary", Duration::from_secs(3600));
+    /// ```
+    pub fn response_cache(mut self, cache: impl ResponseCache + 'static) -> Self {
+        self.response_cache = Some(Arc::new(cache));
+        self
+    }
+
+    /// Sets the time-to-live of cached responses without a matching endpoint TTL
+    ///
+    /// Has no effect without a [response cache](Self::response_cache). Default: 5 minutes.
+    pub fn response_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.response_cache_ttl = ttl;
+        self
+    }
+
+    /// Sets the time-to-live of cached responses of URLs whose path ends with the suffix
+    ///
+    /// The longest matching suffix wins, e.g. `"/summary"` or `"/search-queries/popular"`.
+    /// A zero TTL disables caching of the endpoint. Has no effect without a
+    /// [response cache](Self::response_cache).
+    pub fn endpoint_cache_ttl(mut self, path_suffix: impl Into<String>, ttl: Duration) -> Self {
+        self.endpoint_cache_ttls.push((path_suffix.into(), ttl));
+        self
+    }
+
+    /// Includes the user ID in the log message of a successful [`Self::build`]
+    ///
+    /// Disable it if user IDs are considered personal data in your logs; the message
+    /// is still logged without the ID. Default: `true`.
+    pub fn log_user_id(mut self, value: bool) -> Self {
+        self.log_user_id = value;
+        self
+    }
+
+    /// Sets how many times a GET request is resent after a connection error or timeout
+    ///
+    /// Covers failures without a response, such as refused or reset connections and DNS
+    /// errors. Retries of 429 and 5xx responses are configured separately with middleware
+    /// passed to [`Self::client`]. Zero disables the retries. Default: 1.
+    pub fn connection_retries(mut self, retries: u32) -> Self {
+        self.connection_retries = retries;
+        self
+    }
+
+    /// Emits the spans of API calls as children of the span
+    ///
+    /// Targets of spans and events are fixed at compile time to the crate's module paths,
+    /// so they can't be changed at runtime. Instead, create a span with your own target
+    /// and filter on it, e.g. with the `[yandex]` span directive of `EnvFilter`:
+    ///
+    /// ```
+    /// use yandex_webmaster_api::YandexWebmasterClient;
+    ///
+    /// let span = tracing::info_span!(target: "my_app::yandex", "yandex");
+    /// let builder = YandexWebmasterClient::builder("token".to_string()).parent_span(span);
+    /// ```
+    ///
+    /// Calls made within a span of another call (e.g. pages of `get_all_sitemaps`) stay
+    /// nested in it. Default: the span current at the call site.
+    pub fn parent_span(mut self, span: tracing::Span) -> Self {
+        self.parent_span = Some(span);
+        self
+    }
+
+    /// Sets the IndexNow key used by [`YandexWebmasterClient::index_now`]
+    ///
+    /// The key must be 8 to 128 characters of `a-z`, `A-Z`, `0-9` and `-`, and be
+    /// published on the site, by default as `/{key}.txt` in the site root containing
+    /// the key. It is not related to the OAuth token.
+    pub fn index_now_key(mut self, key: String) -> Self {
+        self.index_now_key = Some(key);
+        self
+    }
+
+    /// Sets the URL of the IndexNow key file if it isn't `/{key}.txt` in the site root
+    ///
+    /// The file must be on the same host as the submitted URLs.
+    pub fn index_now_key_location(mut self, url: String) -> Self {
+        self.index_now_key_location = Some(url);
+        self
+    }
+
+    /// Overrides the IndexNow endpoint (e.g. to point the client at a mock server)
+    ///
+    /// Default: `https://yandex.com/indexnow`.
+    pub fn index_now_url(mut self, url: String) -> Self {
+        self.index_now_url = url;
+        self
+    }
+
+    /// Builds the underlying HTTP client from the connection settings
+    pub(crate) fn build_http_client(&self) -> Result<reqwest::Client> {
+        let client = reqwest::Client::builder()
+            .pool_max_idle_per_host(self.pool_max_idle_per_host)
+            .pool_idle_timeout(self.pool_idle_timeout)
+            .tcp_keepalive(self.tcp_keepalive)
+            .build()?;
+
+        Ok(client)
+    }
+
+    /// Builds the client and fetches the user information
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The HTTP client cannot be created
+    /// - The user information cannot be fetched
+    /// - The OAuth token is invalid
+    pub async fn build(self) -> Result<YandexWebmasterClient> {
+        YandexWebmasterClient::from_builder(self).await
+    }
+
+    /// Builds the client without making any requests
+    ///
+    /// The user ID is fetched on the first call that needs it and cached, so an
+    /// invalid OAuth token is reported by that call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the HTTP client cannot be created.
+    pub fn build_lazy(self) -> Result<YandexWebmasterClient> {
+        YandexWebmasterClient::from_builder_lazy(self)
+    }
+}
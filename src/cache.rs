@@ -0,0 +1,282 @@
+use bytes::Bytes;
+use std::{
+    collections::HashMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::dto::FullHostInfo;
+
+/// In-memory cache of [`FullHostInfo`] keyed by host ID
+#[derive(Debug)]
+pub(crate) struct HostCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, FullHostInfo)>>,
+}
+
+impl HostCache {
+    /// Creates an empty cache with the provided time-to-live of entries
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Returns the cached host information if it isn't expired
+    pub(crate) fn get(&self, host_id: &str) -> Option<FullHostInfo> {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+
+        match entries.get(host_id) {
+            Some((stored_at, info)) if stored_at.elapsed() < self.ttl => Some(info.clone()),
+            Some(_) => {
+                entries.remove(host_id);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Stores the host information
+    pub(crate) fn put(&self, info: FullHostInfo) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.insert(info.host_id.clone(), (Instant::now(), info));
+    }
+
+    /// Removes the host information from the cache
+    pub(crate) fn invalidate(&self, host_id: &str) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.remove(host_id);
+    }
+}
+
+/// Storage for bodies of successful GET responses keyed by the full request URL
+///
+/// Passed to [`crate::YandexWebmasterClientBuilder::response_cache`]. The client looks
+/// up every GET request in the cache before sending it and stores the bodies of
+/// successful responses with the TTL of their endpoint. Mutating requests are never
+/// cached; a successful one [invalidates](Self::invalidate) the responses it may change.
+#[async_trait::async_trait]
+pub trait ResponseCache: Send + Sync {
+    /// Returns the stored body for the URL if it hasn't expired
+    async fn get(&self, url: &str) -> Option<Bytes>;
+
+    /// Stores the body for the URL for `ttl`
+    async fn put(&self, url: &str, body: Bytes, ttl: Duration);
+
+    /// Removes the bodies of all URLs starting with the prefix
+    ///
+    /// The default implementation keeps the entries until they expire.
+    async fn invalidate(&self, url_prefix: &str) {
+        let _ = url_prefix;
+    }
+}
+
+/// In-memory [`ResponseCache`] evicting the least recently used entries
+#[derive(Debug)]
+pub struct InMemoryResponseCache {
+    capacity: usize,
+    state: Mutex<LruState>,
+}
+
+#[derive(Debug, Default)]
+struct LruState {
+    entries: HashMap<String, LruEntry>,
+    tick: u64,
+}
+
+#[derive(Debug)]
+struct LruEntry {
+    body: Bytes,
+    expires_at: Instant,
+    last_used: u64,
+}
+
+impl InMemoryResponseCache {
+    /// Creates an empty cache holding at most `capacity` responses
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            state: Mutex::new(LruState::default()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ResponseCache for InMemoryResponseCache {
+    async fn get(&self, url: &str) -> Option<Bytes> {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tick += 1;
+        let tick = state.tick;
+
+        match state.entries.get_mut(url) {
+            Some(entry) if entry.expires_at > Instant::now() => {
+                entry.last_used = tick;
+                Some(entry.body.clone())
+            }
+            Some(_) => {
+                state.entries.remove(url);
+                None
+            }
+            None => None,
+        }
+    }
+
+    async fn put(&self, url: &str, body: Bytes, ttl: Duration) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.tick += 1;
+        let entry = LruEntry {
+            body,
+            expires_at: Instant::now() + ttl,
+            last_used: state.tick,
+        };
+        state.entries.insert(url.to_string(), entry);
+
+        if state.entries.len() > self.capacity {
+            let least_recent = state
+                .entries
+                .iter()
+                .min_by_key(|(_, entry)| entry.last_used)
+                .map(|(url, _)| url.clone());
+            if let Some(url) = least_recent {
+                state.entries.remove(&url);
+            }
+        }
+    }
+
+    async fn invalidate(&self, url_prefix: &str) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.entries.retain(|url, _| !url.starts_with(url_prefix));
+    }
+}
+
+/// Response cache of a client with the TTLs of its endpoints
+#[derive(Clone)]
+pub(crate) struct ResponseCachePolicy {
+    pub(crate) cache: Arc<dyn ResponseCache>,
+    pub(crate) default_ttl: Duration,
+    pub(crate) endpoint_ttls: Vec<(String, Duration)>,
+}
+
+impl ResponseCachePolicy {
+    /// Returns the TTL for the URL, `None` if its responses shouldn't be cached
+    ///
+    /// The longest endpoint path suffix matching the URL path wins.
+    pub(crate) fn ttl(&self, url: &str) -> Option<Duration> {
+        let path = url.split('?').next().unwrap_or(url);
+        let ttl = self
+            .endpoint_ttls
+            .iter()
+            .filter(|(suffix, _)| path.ends_with(suffix.as_str()))
+            .max_by_key(|(suffix, _)| suffix.len())
+            .map_or(self.default_ttl, |(_, ttl)| *ttl);
+
+        (!ttl.is_zero()).then_some(ttl)
+    }
+}
+
+impl fmt::Debug for ResponseCachePolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ResponseCachePolicy")
+            .field("default_ttl", &self.default_ttl)
+            .field("endpoint_ttls", &self.endpoint_ttls)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(host_id: &str) -> FullHostInfo {
+        FullHostInfo {
+            host_id: host_id.to_string(),
+            ascii_host_url: "https://example.com/".to_string(),
+            unicode_host_url: "https://example.com/".to_string(),
+            verified: true,
+            main_mirror: None,
+            host_data_status: None,
+            host_display_name: None,
+        }
+    }
+
+    #[test]
+    fn test_host_cache_invalidate() {
+        let cache = HostCache::new(Duration::from_secs(60));
+        cache.put(host("https:example.com:443"));
+
+        assert!(cache.get("https:example.com:443").is_some());
+        assert!(cache.get("http:example.com:80").is_none());
+
+        cache.invalidate("https:example.com:443");
+        assert!(cache.get("https:example.com:443").is_none());
+    }
+
+    #[test]
+    fn test_host_cache_expired() {
+        let cache = HostCache::new(Duration::ZERO);
+        cache.put(host("https:example.com:443"));
+
+        assert!(cache.get("https:example.com:443").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_in_memory_response_cache_lru() {
+        let cache = InMemoryResponseCache::new(2);
+        let ttl = Duration::from_secs(60);
+        cache.put("/a", Bytes::from_static(b"a"), ttl).await;
+        cache.put("/b", Bytes::from_static(b"b"), ttl).await;
+
+        // Reading `/a` makes `/b` the least recently used entry
+        assert_eq!(cache.get("/a").await, Some(Bytes::from_static(b"a")));
+        cache.put("/c", Bytes::from_static(b"c"), ttl).await;
+
+        assert!(cache.get("/a").await.is_some());
+        assert!(cache.get("/b").await.is_none());
+        assert!(cache.get("/c").await.is_some());
+
+        cache
+            .put("/d", Bytes::from_static(b"d"), Duration::ZERO)
+            .await;
+        assert!(cache.get("/d").await.is_none());
+
+        cache.invalidate("/a").await;
+        assert!(cache.get("/a").await.is_none());
+        assert!(cache.get("/c").await.is_some());
+    }
+
+    #[test]
+    fn test_response_cache_policy_ttl() {
+        let policy = ResponseCachePolicy {
+            cache: Arc::new(InMemoryResponseCache::new(1)),
+            default_ttl: Duration::from_secs(60),
+            endpoint_ttls: vec![
+                ("/summary".to_string(), Duration::from_secs(3600)),
+                ("/sqi-history".to_string(), Duration::ZERO),
+                ("/hosts/h/summary".to_string(), Duration::from_secs(10)),
+            ],
+        };
+
+        assert_eq!(
+            policy.ttl("https://api/user/1/hosts/h/summary"),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            policy.ttl("https://api/user/1/hosts/g/summary"),
+            Some(Duration::from_secs(3600))
+        );
+        assert_eq!(
+            policy.ttl("https://api/user/1/hosts/h/sqi-history?a=1"),
+            None
+        );
+        assert_eq!(
+            policy.ttl("https://api/user/1/hosts/h/sitemaps?summary"),
+            Some(Duration::from_secs(60))
+        );
+    }
+}
@@ -1,23 +1,104 @@
+use chrono::{DateTime, Utc};
+use futures::{future, stream, Stream, StreamExt, TryStreamExt};
+use reqwest::header::HeaderMap;
 use reqwest_middleware::ClientBuilder;
 use serde_json::json;
-use serde_qs::ArrayFormat;
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::Write,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::{OnceCell, Semaphore};
 use tracing::instrument;
 
 use crate::{
+    builder::YandexWebmasterClientBuilder,
+    cache::{HostCache, ResponseCachePolicy},
     dto::*,
-    error::{Result, YandexApiErrorResponse, YandexWebmasterError},
-    middleware::AuthMiddleware,
+    error::{PartialResult, Result, YandexApiErrorResponse, YandexErrorCode, YandexWebmasterError},
+    host_client::HostClient,
+    middleware::{
+        AuthMiddleware, ConnectionRetryMiddleware, LanguageMiddleware, SlowRequestMiddleware,
+        Unauthenticated,
+    },
+    parse::parse_response_from,
+    serde_utils::query_config,
 };
 
 /// Base URL for the Yandex Webmaster API
-const API_BASE_URL: &str = "https://api.webmaster.yandex.net/v4";
+pub(crate) const API_BASE_URL: &str = "https://api.webmaster.yandex.net/v4";
+
+/// IndexNow endpoint of Yandex
+pub(crate) const INDEX_NOW_URL: &str = "https://yandex.com/indexnow";
+
+/// Maximum number of URLs in an IndexNow submission
+const INDEX_NOW_MAX_URLS: usize = 10_000;
+
+/// Known endpoints of the Yandex Webmaster API
+///
+/// Only the global endpoint is documented by Yandex at the moment. Use
+/// [`YandexWebmasterClientBuilder::base_url`] for mirrors and proxies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[non_exhaustive]
+pub enum ApiRegion {
+    /// `https://api.webmaster.yandex.net/v4`
+    #[default]
+    Global,
+}
+
+impl ApiRegion {
+    /// Returns the base URL of the endpoint
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            ApiRegion::Global => API_BASE_URL,
+        }
+    }
+}
+
+/// Maximum number of concurrent requests made by bulk operations
+pub(crate) const BULK_CONCURRENCY: usize = 4;
+
+/// Content type of JSON request bodies
+const JSON_CONTENT_TYPE: &str = "application/json; charset=utf-8";
+
+/// Length of the date range requested at once by `get_full_sqi_history`
+const SQI_HISTORY_WINDOW: chrono::Duration = chrono::Duration::days(90);
+
+/// Header carrying the request ID
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Delay after a rate limit error without a `Retry-After` header
+const DEFAULT_RATE_LIMIT_DELAY: Duration = Duration::from_secs(60);
 
 /// Client for interacting with the Yandex Webmaster API
 #[derive(Debug, Clone)]
 pub struct YandexWebmasterClient {
     client: reqwest_middleware::ClientWithMiddleware,
-    user_id: i64,
+    base_url: String,
+    user_id: Arc<OnceCell<i64>>,
     qs: serde_qs::Config,
+    treat_already_added_as_success: bool,
+    host_cache: Option<Arc<HostCache>>,
+    compression_threshold: Option<usize>,
+    max_body_size: Option<usize>,
+    compression_rejected: Arc<AtomicBool>,
+    generate_request_ids: bool,
+    request_id: Option<String>,
+    content_type: Option<String>,
+    extra_headers: Option<Arc<HeaderMap>>,
+    strict_deserialization: bool,
+    request_limiter: Option<Arc<Semaphore>>,
+    rate_limited_until: Arc<Mutex<Option<Instant>>>,
+    response_cache: Option<ResponseCachePolicy>,
+    parent_span: Option<tracing::Span>,
+    index_now_key: Option<String>,
+    index_now_key_location: Option<String>,
+    index_now_url: String,
 }
 
 impl YandexWebmasterClient {
@@ -40,24 +121,7 @@ impl YandexWebmasterClient {
     /// - The OAuth token is invalid
     #[instrument(skip(oauth_token))]
     pub async fn new(oauth_token: String) -> Result<Self> {
-        // Build the HTTP client with middleware
-        let client = ClientBuilder::new(reqwest::Client::new())
-            .with(AuthMiddleware::new(oauth_token))
-            .build();
-
-        // Fetch user information
-        let user_response = Self::fetch_user(&client).await?;
-
-        tracing::info!(
-            user_id = user_response.user_id,
-            "Successfully authenticated"
-        );
-
-        Ok(Self {
-            client,
-            user_id: user_response.user_id,
-            qs: serde_qs::Config::new().array_format(ArrayFormat::Unindexed),
-        })
+        Self::builder(oauth_token).build().await
     }
 
     /// Creates a new Yandex Webmaster API client
@@ -75,45 +139,306 @@ impl YandexWebmasterClient {
     /// - The OAuth token is invalid
     #[instrument(skip(oauth_token, client))]
     pub async fn with_client(oauth_token: String, client: ClientBuilder) -> Result<Self> {
-        // Build the HTTP client with middleware
-        let client = client.with(AuthMiddleware::new(oauth_token)).build();
+        Self::builder(oauth_token).client(client).build().await
+    }
+
+    /// Creates a builder for configuring the client
+    ///
+    /// # Arguments
+    ///
+    /// * `oauth_token` - OAuth token for authentication
+    pub fn builder(oauth_token: String) -> YandexWebmasterClientBuilder {
+        YandexWebmasterClientBuilder::new(oauth_token)
+    }
+
+    /// Creates a client from the builder configuration
+    #[instrument(skip(builder))]
+    pub(crate) async fn from_builder(builder: YandexWebmasterClientBuilder) -> Result<Self> {
+        let log_user_id = builder.log_user_id;
+        let client = Self::from_builder_lazy(builder)?;
 
         // Fetch user information
-        let user_response = Self::fetch_user(&client).await?;
+        let user_id = client.get_user_id().await?;
 
-        tracing::info!(
-            user_id = user_response.user_id,
-            "Successfully authenticated"
-        );
+        if log_user_id {
+            tracing::info!(user_id, "Successfully authenticated");
+        } else {
+            tracing::info!("Successfully authenticated");
+        }
 
-        Ok(Self {
-            client,
-            user_id: user_response.user_id,
-            qs: serde_qs::Config::new().array_format(ArrayFormat::Unindexed),
-        })
+        Ok(client)
     }
 
-    /// Fetches user information from the API
-    #[instrument(skip(client))]
-    async fn fetch_user(client: &reqwest_middleware::ClientWithMiddleware) -> Result<UserResponse> {
-        let url = format!("{}/user", API_BASE_URL);
+    /// Creates a client from the builder configuration without fetching the user
+    pub(crate) fn from_builder_lazy(mut builder: YandexWebmasterClientBuilder) -> Result<Self> {
+        let client = Self::build_client(&mut builder)?;
+        Ok(Self::from_parts(builder, client, None))
+    }
 
-        tracing::debug!(url = %url, "Fetching user information");
+    /// Builds the HTTP client with middleware from the builder configuration
+    pub(crate) fn build_client(
+        builder: &mut YandexWebmasterClientBuilder,
+    ) -> Result<reqwest_middleware::ClientWithMiddleware> {
+        let mut client = match builder.client.take() {
+            Some(client) => client,
+            None => ClientBuilder::new(builder.build_http_client()?),
+        };
 
-        let response = client.get(&url).send().await?;
+        if let Some(threshold) = builder.slow_request_threshold {
+            client = client.with(SlowRequestMiddleware::new(threshold));
+        }
 
-        if !response.status().is_success() {
-            return Err(Self::parse_error(response).await);
+        if builder.connection_retries > 0 {
+            client = client.with(ConnectionRetryMiddleware::new(builder.connection_retries));
+        }
+
+        let mut auth = AuthMiddleware::new(builder.oauth_token.clone());
+        if let Some(refresher) = builder.token_refresher.take() {
+            auth = auth.with_refresher(refresher);
+        }
+        client = client.with(auth);
+
+        if let Some(language) = &builder.language {
+            client = client.with(LanguageMiddleware::new(language.clone()));
+        }
+
+        Ok(client.build())
+    }
+
+    /// Creates a client for the user, `None` fetches the user ID on first use
+    pub(crate) fn from_parts(
+        builder: YandexWebmasterClientBuilder,
+        client: reqwest_middleware::ClientWithMiddleware,
+        user_id: Option<i64>,
+    ) -> Self {
+        Self {
+            client,
+            base_url: builder.base_url,
+            user_id: Arc::new(OnceCell::new_with(user_id)),
+            qs: query_config(),
+            treat_already_added_as_success: builder.treat_already_added_as_success,
+            host_cache: builder
+                .host_cache_ttl
+                .map(|ttl| Arc::new(HostCache::new(ttl))),
+            compression_threshold: builder
+                .compress_requests
+                .then_some(builder.compression_threshold),
+            max_body_size: builder.max_body_size,
+            compression_rejected: Arc::new(AtomicBool::new(false)),
+            generate_request_ids: builder.request_id,
+            request_id: None,
+            content_type: None,
+            extra_headers: None,
+            strict_deserialization: builder.strict_deserialization,
+            request_limiter: builder
+                .max_concurrent_requests
+                .filter(|&permits| permits > 0)
+                .map(|permits| Arc::new(Semaphore::new(permits))),
+            rate_limited_until: Arc::new(Mutex::new(None)),
+            response_cache: builder.response_cache.map(|cache| ResponseCachePolicy {
+                cache,
+                default_ttl: builder.response_cache_ttl,
+                endpoint_ttls: builder.endpoint_cache_ttls,
+            }),
+            parent_span: builder.parent_span,
+            index_now_key: builder.index_now_key,
+            index_now_key_location: builder.index_now_key_location,
+            index_now_url: builder.index_now_url,
         }
+    }
+
+    /// Returns the parent of the span of an API call
+    ///
+    /// Calls made from the span of another call stay nested in it, other calls go under
+    /// the [configured parent span](YandexWebmasterClientBuilder::parent_span).
+    pub(crate) fn span_parent(&self) -> Option<tracing::Id> {
+        let current = tracing::Span::current();
+        let Some(parent) = &self.parent_span else {
+            return current.id();
+        };
+
+        let in_crate_span = current.metadata().is_some_and(|metadata| {
+            metadata
+                .target()
+                .split("::")
+                .next()
+                .is_some_and(|krate| krate == env!("CARGO_CRATE_NAME"))
+        });
+        if in_crate_span {
+            current.id()
+        } else {
+            parent.id()
+        }
+    }
+
+    /// Fetches user information from the API
+    #[instrument(skip(self))]
+    async fn fetch_user(&self) -> Result<UserResponse> {
+        let url = format!("{}/user", self.base_url);
 
-        let user_response: UserResponse = response.json().await?;
+        tracing::debug!(url = %url, "Fetching user information");
 
-        Ok(user_response)
+        self.get(&url).await
     }
 
     /// Returns the user ID
+    ///
+    /// Clients created with [`Self::new`] or [`YandexWebmasterClientBuilder::build`] fetch
+    /// the ID on creation, so it is always set. Only clients created with
+    /// [`YandexWebmasterClientBuilder::build_lazy`] return 0 until the first request
+    /// fetches it; use [`Self::get_user_id`] for them.
     pub fn user_id(&self) -> i64 {
+        self.user_id.get().copied().unwrap_or_default()
+    }
+
+    /// Returns the user ID, fetching it on first call for lazily built clients
+    ///
+    /// The fetched ID is cached and shared by clones of the client.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_user_id(&self) -> Result<i64> {
         self.user_id
+            .get_or_try_init(|| async { Ok(self.fetch_user().await?.user_id) })
+            .await
+            .copied()
+    }
+
+    /// Builds the URL of the host resource, rejecting malformed host IDs before any request
+    async fn host_url(&self, host_id: &str) -> Result<String> {
+        validate_host_id(host_id)?;
+        Ok(format!(
+            "{}/user/{}/hosts/{}",
+            self.base_url,
+            self.get_user_id().await?,
+            path_segment(host_id)
+        ))
+    }
+
+    /// Returns a copy of the client that sends the provided request ID with every request
+    ///
+    /// The ID is sent in the `X-Request-Id` header and returned by
+    /// [`YandexWebmasterError::request_id`], which helps to trace a failed call.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: yandex_webmaster_api::YandexWebmasterClient) {
+    /// let host = client
+    ///     .with_request_id("import-42".to_string())
+    ///     .get_host("https:example.com:443")
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn with_request_id(&self, request_id: String) -> Self {
+        Self {
+            request_id: Some(request_id),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the client that sends request bodies with the content type
+    ///
+    /// Bodies are still serialized as JSON, so this is meant for the JSON content types
+    /// an endpoint lists in [`YandexWebmasterError::ContentTypeUnsupported`].
+    /// Default: `application/json; charset=utf-8`.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: yandex_webmaster_api::YandexWebmasterClient) {
+    /// let response = client
+    ///     .with_content_type("application/json".to_string())
+    ///     .recrawl_urls("https:example.com:443", "https://example.com/page")
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn with_content_type(&self, content_type: String) -> Self {
+        Self {
+            content_type: Some(content_type),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the client that doesn't use the response cache
+    ///
+    /// Responses are neither read from nor stored in the
+    /// [response cache](YandexWebmasterClientBuilder::response_cache).
+    pub fn without_response_cache(&self) -> Self {
+        Self {
+            response_cache: None,
+            ..self.clone()
+        }
+    }
+
+    /// Returns a copy of the client that sends the provided headers with every request
+    ///
+    /// Headers are added to those of the previous calls of this method, replacing values
+    /// with the same name. The `Authorization` header can't be overridden this way: it is
+    /// always set from the client's OAuth token.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: yandex_webmaster_api::YandexWebmasterClient) {
+    /// use reqwest::header::{HeaderMap, HeaderValue};
+    ///
+    /// let mut headers = HeaderMap::new();
+    /// headers.insert("X-Tenant-Id", HeaderValue::from_static("acme"));
+    /// let host = client
+    ///     .with_headers(headers)
+    ///     .get_host("https:example.com:443")
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn with_headers(&self, headers: HeaderMap) -> Self {
+        let mut extra_headers = self.extra_headers.as_deref().cloned().unwrap_or_default();
+        extra_headers.extend(headers);
+        extra_headers.remove(reqwest::header::AUTHORIZATION);
+
+        Self {
+            extra_headers: Some(Arc::new(extra_headers)),
+            ..self.clone()
+        }
+    }
+
+    /// Returns a client bound to the host
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: yandex_webmaster_api::YandexWebmasterClient) -> yandex_webmaster_api::Result<()> {
+    /// let host = client.host("https:example.com:443");
+    /// let summary = host.summary().await?;
+    /// let diagnostics = host.diagnostics().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn host(&self, host_id: &str) -> HostClient {
+        HostClient::new(self.clone(), host_id.to_string())
+    }
+
+    /// Returns the rate limit state of the client and its clones
+    ///
+    /// The API doesn't report the remaining budget, so the client is considered
+    /// exhausted after a `429 Too Many Requests` response until its `Retry-After` delay
+    /// (60 seconds if absent) passes. Bulk methods check it before sending requests.
+    ///
+    /// There is no endpoint for the daily usage of the account either: only the recrawl
+    /// quota is reported, see [`Self::get_recrawl_quota`].
+    pub fn check_rate_limit(&self) -> RateLimitStatus {
+        let mut until = self
+            .rate_limited_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        match *until {
+            Some(instant) if instant > Instant::now() => RateLimitStatus::Exhausted {
+                retry_after: instant - Instant::now(),
+            },
+            _ => {
+                *until = None;
+                RateLimitStatus::Available
+            }
+        }
     }
 
     // ============================================================================
@@ -121,40 +446,284 @@ impl YandexWebmasterClient {
     // ============================================================================
 
     /// List all sites for the user
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_hosts(&self) -> Result<Vec<HostInfo>> {
-        let url = format!("{}/user/{}/hosts", API_BASE_URL, self.user_id);
+        let url = format!("{}/user/{}/hosts", self.base_url, self.get_user_id().await?);
         let result: HostsResponse = self.get(&url).await?;
         Ok(result.hosts)
     }
 
+    /// Get the first verified site of the user
+    ///
+    /// Returns [`YandexWebmasterError::NoVerifiedHosts`] if the user has no verified sites.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_primary_verified_host(&self) -> Result<HostInfo> {
+        self.get_hosts()
+            .await?
+            .into_iter()
+            .find(|host| host.verified)
+            .ok_or(YandexWebmasterError::NoVerifiedHosts)
+    }
+
     /// Add a new site
-    #[instrument(skip(self))]
+    ///
+    /// If the client is built with `treat_already_added_as_success(true)`, a
+    /// `HOST_ALREADY_ADDED` error is returned as success with the existing host ID.
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn add_host(
         &self,
         host_url: &str,
         verification_type: VerificationType,
     ) -> Result<AddHostResponse> {
-        let url = format!("{}/user/{}/hosts", API_BASE_URL, self.user_id);
-        self.post(
-            &url,
-            &json!({ "host_url": host_url.to_string(), "verification_type": verification_type }),
-        )
-        .await
+        let url = format!("{}/user/{}/hosts", self.base_url, self.get_user_id().await?);
+        let result = self
+            .post(
+                &url,
+                &json!({ "host_url": host_url.to_string(), "verification_type": verification_type }),
+            )
+            .await;
+
+        let existing = match &result {
+            Err(YandexWebmasterError::ApiError { response, .. })
+                if response.error_code == YandexErrorCode::HostAlreadyAdded =>
+            {
+                response.host_id.clone()
+            }
+            _ => None,
+        };
+        match existing {
+            Some(host_id) if self.treat_already_added_as_success => {
+                tracing::debug!(host_id = %host_id, "Host is already added");
+                Ok(AddHostResponse { host_id })
+            }
+            _ => result,
+        }
+    }
+
+    /// Add a new site and pick the quickest way to verify the rights to it
+    ///
+    /// The site is added with meta tag verification, then the
+    /// [recommended](HostVerificationResponse::recommended_verifier) of the applicable
+    /// methods is returned with its token. Place the [detail](VerificationDetail) on the
+    /// site and call [`Self::verify_host`] with the returned method.
+    ///
+    /// Returns [`YandexWebmasterError::NoApplicableVerifiers`] if the site can't be
+    /// verified with any explicit method, e.g. because it is already verified.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn add_host_auto_verify(&self, host_url: &str) -> Result<VerificationInstructions> {
+        let host_id = self
+            .add_host(host_url, VerificationType::MetaTag)
+            .await?
+            .host_id;
+        let status = self.get_verification_status(&host_id).await?;
+
+        let Some(verification_type) = status.recommended_verifier() else {
+            return Err(YandexWebmasterError::NoApplicableVerifiers(host_id));
+        };
+        Ok(VerificationInstructions {
+            detail: VerificationDetail::new(verification_type, &status.verification_uin),
+            host_id,
+            verification_type,
+            verification_uin: status.verification_uin,
+        })
     }
 
     /// Get information about a specific site
-    #[instrument(skip(self))]
+    ///
+    /// Always requests fresh data, see [`Self::get_host_cached`] for the cached variant.
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_host(&self, host_id: &str) -> Result<FullHostInfo> {
-        let url = format!("{}/user/{}/hosts/{}", API_BASE_URL, self.user_id, host_id);
-        self.get(&url).await
+        let url = self.host_url(host_id).await?;
+        let info: FullHostInfo = self.get_uncached(&url).await?;
+
+        if let Some(cache) = &self.host_cache {
+            cache.put(info.clone());
+        }
+
+        Ok(info)
+    }
+
+    /// Get detailed information about all sites of the user
+    ///
+    /// Sites are fetched concurrently (at most 4 requests at a time). A site that fails
+    /// to load is reported in [`PartialResult::errors`] with its host ID instead of
+    /// failing the whole call.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_hosts_detailed(&self) -> Result<PartialResult<FullHostInfo>> {
+        let hosts = self.get_hosts().await?;
+
+        Ok(stream::iter(hosts)
+            .map(|host| async move {
+                let result = self.get_host(&host.host_id).await;
+                (host.host_id, result)
+            })
+            .buffered(BULK_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect())
+    }
+
+    /// Get all sites of the user with their verification states
+    ///
+    /// Verified sites need no extra requests. The state of other sites is fetched with
+    /// [`Self::get_verification_status`] concurrently (at most 4 requests at a time). A
+    /// site whose state fails to load is reported in [`PartialResult::errors`] with its
+    /// host ID instead of failing the whole call. Sites are returned in the order of the
+    /// hosts list.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::collections::HashMap;
+    /// # use yandex_webmaster_api::*;
+    /// # async fn example(client: YandexWebmasterClient) -> Result<()> {
+    /// let mut grouped: HashMap<VerificationState, Vec<HostInfo>> = HashMap::new();
+    /// for (state, host) in client.get_hosts_by_verification().await?.ok {
+    ///     grouped.entry(state).or_default().push(host);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_hosts_by_verification(
+        &self,
+    ) -> Result<PartialResult<(VerificationState, HostInfo)>> {
+        let hosts = self.get_hosts().await?;
+
+        Ok(stream::iter(hosts)
+            .map(|host| async move {
+                let state = if host.verified {
+                    Ok(VerificationState::Verified)
+                } else {
+                    self.get_verification_status(&host.host_id)
+                        .await
+                        .map(|status| status.verification_state)
+                };
+                (host.host_id.clone(), state.map(|state| (state, host)))
+            })
+            .buffered(BULK_CONCURRENCY)
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect())
+    }
+
+    /// Get information about a specific site, returning `None` if it doesn't exist
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_host_opt(&self, host_id: &str) -> Result<Option<FullHostInfo>> {
+        Self::not_found_to_none(self.get_host(host_id).await)
+    }
+
+    /// Get information about a specific site using the in-memory cache
+    ///
+    /// The cache is enabled with the `host_cache_ttl` builder option. If it is
+    /// disabled, this method is equivalent to [`Self::get_host`].
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_host_cached(&self, host_id: &str) -> Result<FullHostInfo> {
+        if let Some(info) = self.host_cache.as_ref().and_then(|c| c.get(host_id)) {
+            tracing::debug!(host_id = %host_id, "Using cached host information");
+            return Ok(info);
+        }
+
+        self.get_host(host_id).await
+    }
+
+    /// Check whether the site is migrated to HTTPS
+    ///
+    /// Combines the scheme of the site and its main mirror with the
+    /// `MAIN_MIRROR_IS_NOT_HTTPS` problem from the site diagnostics.
+    ///
+    /// The `ONLY_HTTPS` error code is not part of the status: the API returns it only
+    /// when an HTTP feed URL is added, so it can't be checked without adding a feed.
+    /// Handle it as [`YandexErrorCode::OnlyHttps`] of [`Self::add_feed`] or
+    /// [`FeedStatus::OnlyHttps`] of [`Self::get_feed_add_status`] instead.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn check_https_migration(&self, host_id: &str) -> Result<HttpsMigrationStatus> {
+        let (host, diagnostics) =
+            futures::try_join!(self.get_host(host_id), self.get_diagnostics(host_id))?;
+
+        let host_is_https = host.is_https();
+        let main_mirror_is_https = host
+            .main_mirror
+            .as_ref()
+            .map_or(host_is_https, |mirror| mirror.is_https());
+        let main_mirror_is_not_https_problem = diagnostics
+            .problems
+            .get(&ApiSiteProblemTypeEnum::MainMirrorIsNotHttps)
+            .is_some_and(|problem| problem.state == ApiSiteProblemState::Present);
+
+        Ok(HttpsMigrationStatus {
+            host_is_https,
+            main_mirror_is_https,
+            main_mirror_is_not_https_problem,
+        })
     }
 
     /// Delete a site
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn delete_host(&self, host_id: &str) -> Result<()> {
-        let url = format!("{}/user/{}/hosts/{}", API_BASE_URL, self.user_id, host_id);
-        self.delete(&url).await
+        let url = self.host_url(host_id).await?;
+        self.delete(&url).await?;
+
+        if let Some(cache) = &self.host_cache {
+            cache.invalidate(host_id);
+        }
+
+        Ok(())
+    }
+
+    /// Delete several sites
+    ///
+    /// Sites are deleted concurrently (at most 4 requests at a time) and the result for
+    /// every ID is returned in the input order. A `HOST_NOT_FOUND` error counts as success
+    /// since the site is already gone. If the number of IDs doesn't match the
+    /// `confirmation`, every ID fails with `InvalidRequest` and nothing is deleted. Once
+    /// the client is [rate limited](Self::check_rate_limit), the remaining sites fail
+    /// with `RateLimitExhausted` without sending requests.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use yandex_webmaster_api::*;
+    /// # async fn example(client: YandexWebmasterClient) {
+    /// let host_ids = ["https:old.example.com:443", "http:old.example.com:80"];
+    /// let results = client
+    ///     .delete_hosts(&host_ids, DeleteConfirmation::count(2))
+    ///     .await;
+    /// # }
+    /// ```
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn delete_hosts(
+        &self,
+        host_ids: &[&str],
+        confirmation: DeleteConfirmation,
+    ) -> Vec<(String, Result<()>)> {
+        if confirmation.check(host_ids.len()).is_err() {
+            return host_ids
+                .iter()
+                .map(|host_id| (host_id.to_string(), confirmation.check(host_ids.len())))
+                .collect();
+        }
+
+        stream::iter(host_ids)
+            .map(|host_id| async move {
+                let result = match self.ensure_rate_limit_available() {
+                    Ok(()) => self.delete_host(host_id).await,
+                    Err(e) => Err(e),
+                };
+                let result = match result {
+                    Err(e) if e.error_code() == Some(&YandexErrorCode::HostNotFound) => {
+                        tracing::debug!(host_id = %host_id, "Host is already deleted");
+                        Ok(())
+                    }
+                    result => result,
+                };
+                (host_id.to_string(), result)
+            })
+            .buffered(BULK_CONCURRENCY)
+            .collect()
+            .await
     }
 
     // ============================================================================
@@ -162,39 +731,47 @@ impl YandexWebmasterClient {
     // ============================================================================
 
     /// Get verification status for a site
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_verification_status(&self, host_id: &str) -> Result<HostVerificationResponse> {
-        let url = format!(
-            "{}/user/{}/hosts/{}/verification",
-            API_BASE_URL, self.user_id, host_id
-        );
+        let url = format!("{}/verification", self.host_url(host_id).await?);
         self.get(&url).await
     }
 
+    /// Get the verification methods the site can use
+    ///
+    /// Reads the [verification status](Self::get_verification_status) without starting
+    /// a verification, so it can be called before choosing the method for
+    /// [`Self::verify_host`].
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_applicable_verifiers(
+        &self,
+        host_id: &str,
+    ) -> Result<Vec<ExplicitVerificationType>> {
+        Ok(self
+            .get_verification_status(host_id)
+            .await?
+            .applicable_verifiers)
+    }
+
     /// Initiate verification procedure for a site
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn verify_host(
         &self,
         host_id: &str,
         verification_type: ExplicitVerificationType,
     ) -> Result<HostVerificationResponse> {
-        let serialized = serde_json::to_value(verification_type)?;
-        let verification_type = serialized.clone().as_str().unwrap_or("").to_owned();
-
         let url = format!(
-            "{}/user/{}/hosts/{}/verification?verification_type={}",
-            API_BASE_URL, self.user_id, host_id, verification_type
+            "{}/verification?verification_type={}",
+            self.host_url(host_id).await?,
+            verification_type.as_query_value()
         );
         self.post(&url, &()).await
     }
 
     /// Get list of verified owners for a site
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_owners(&self, host_id: &str) -> Result<Vec<Owner>> {
-        let url = format!(
-            "{}/user/{}/hosts/{}/owners",
-            API_BASE_URL, self.user_id, host_id
-        );
+        let url = format!("{}/owners", self.host_url(host_id).await?);
         let result: OwnersResponse = self.get(&url).await?;
         Ok(result.users)
     }
@@ -204,426 +781,1198 @@ impl YandexWebmasterClient {
     // ============================================================================
 
     /// Get site summary statistics
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_host_summary(&self, host_id: &str) -> Result<HostSummaryResponse> {
-        let url = format!(
-            "{}/user/{}/hosts/{}/summary",
-            API_BASE_URL, self.user_id, host_id
-        );
+        let url = format!("{}/summary", self.host_url(host_id).await?);
         self.get(&url).await
     }
 
     /// Get site quality index history
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_sqi_history(
         &self,
         host_id: &str,
         req: SqiHistoryRequest,
     ) -> Result<Vec<SqiPoint>> {
         let url = format!(
-            "{}/user/{}/hosts/{}/sqi-history?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/sqi-history?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(&req)?
         );
         let result: SqiHistoryResponse = self.get(&url).await?;
         Ok(result.points)
     }
 
+    /// Get site quality index history for a long date range
+    ///
+    /// The range is requested in 90-day windows one after another. Windows rejected with
+    /// `SOME_DATES_ARE_UNAVAILABLE` are skipped. The points are merged with
+    /// [`SqiHistoryResponse::normalized`].
+    ///
+    /// Returns [`YandexWebmasterError::InvalidRequest`] if `date_from` is after `date_to`.
+    /// Once the client is [rate limited](Self::check_rate_limit), no more windows are
+    /// requested and the call fails with `RateLimitExhausted`, discarding the windows
+    /// already fetched.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_full_sqi_history(
+        &self,
+        host_id: &str,
+        date_from: DateTime<Utc>,
+        date_to: DateTime<Utc>,
+    ) -> Result<Vec<SqiPoint>> {
+        if date_from > date_to {
+            return Err(YandexWebmasterError::InvalidRequest(format!(
+                "date_from {} is after date_to {}",
+                date_from, date_to
+            )));
+        }
+
+        let mut points = Vec::new();
+        let mut window_start = date_from;
+        loop {
+            let window_end = (window_start + SQI_HISTORY_WINDOW).min(date_to);
+            self.ensure_rate_limit_available()?;
+
+            let request = SqiHistoryRequest {
+                date_from: Some(window_start),
+                date_to: Some(window_end),
+            };
+            match self.get_sqi_history(host_id, request).await {
+                Ok(window) => points.extend(window),
+                Err(e) if e.error_code() == Some(&YandexErrorCode::SomeDatesAreUnavailable) => {
+                    tracing::debug!(
+                        date_from = %window_start,
+                        date_to = %window_end,
+                        "Skipping unavailable SQI history window"
+                    );
+                }
+                Err(e) => return Err(e),
+            }
+
+            if window_end >= date_to {
+                break;
+            }
+            window_start = window_end;
+        }
+
+        Ok(SqiHistoryResponse { points }.normalized())
+    }
+
     // ============================================================================
     // Search Queries
     // ============================================================================
 
     /// Get popular search queries for a site
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_popular_queries(
         &self,
         host_id: &str,
         request: &PopularQueriesRequest,
     ) -> Result<PopularQueriesResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/search-queries/popular?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/search-queries/popular?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
+    /// Get all popular search queries containing the substring, paging through the whole list
+    ///
+    /// The API has no text filter for popular queries, so every page is fetched and
+    /// `query_text` is matched case-insensitively on the client. The `offset` and `limit`
+    /// of the request are overridden; the order of the queries is kept.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_popular_queries_matching(
+        &self,
+        host_id: &str,
+        request: &PopularQueriesRequest,
+        substr: &str,
+    ) -> Result<Vec<PopularQuery>> {
+        const PAGE_SIZE: i32 = 500;
+
+        let substr = substr.to_lowercase();
+        let mut offset = 0;
+        let mut queries = Vec::new();
+
+        loop {
+            let request = PopularQueriesRequest {
+                offset: Some(offset),
+                limit: Some(PAGE_SIZE),
+                ..request.clone()
+            };
+            let page = self.get_popular_queries(host_id, &request).await?;
+            let page_len = page.queries.len();
+            offset += page_len as i32;
+
+            queries.extend(
+                page.queries
+                    .into_iter()
+                    .filter(|query| query.query_text.to_lowercase().contains(&substr)),
+            );
+
+            if page_len < PAGE_SIZE as usize || offset >= page.count {
+                break;
+            }
+        }
+
+        Ok(queries)
+    }
+
+    /// Get popular search queries for several device types
+    ///
+    /// Makes one request per device type concurrently, overriding the
+    /// `device_type_indicator` of the request. Fails if any of the requests fails.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_popular_queries_by_device(
+        &self,
+        host_id: &str,
+        request: &PopularQueriesRequest,
+        devices: &[ApiDeviceTypeIndicator],
+    ) -> Result<HashMap<ApiDeviceTypeIndicator, PopularQueriesResponse>> {
+        self.ensure_rate_limit_available()?;
+
+        let requests = devices.iter().map(|&device| async move {
+            let request = PopularQueriesRequest {
+                device_type_indicator: Some(device),
+                ..request.clone()
+            };
+            let response = self.get_popular_queries(host_id, &request).await?;
+            Ok::<_, YandexWebmasterError>((device, response))
+        });
+
+        Ok(future::try_join_all(requests).await?.into_iter().collect())
+    }
+
     /// Get overall query statistics history
-    #[instrument(skip(self))]
+    ///
+    /// Fails with [`YandexWebmasterError::InvalidRequest`] without calling the API if
+    /// `query_indicator` is empty.
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_query_analytics(
         &self,
         host_id: &str,
         request: &QueryAnalyticsRequest,
     ) -> Result<QueryAnalyticsResponse> {
+        request.validate()?;
+
         let url = format!(
-            "{}/user/{}/hosts/{}/search-queries/all/history?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/search-queries/all/history?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
+    /// Get overall query statistics history for a long date range in chunks
+    ///
+    /// The range of the request is split into chunks of the given length, fetched
+    /// concurrently (at most 4 requests at a time) and merged with
+    /// [`QueryAnalyticsResponse::merge`]. Chunks rejected with `SOME_DATES_ARE_UNAVAILABLE`
+    /// are skipped. Fails with [`YandexWebmasterError::InvalidRequest`] without calling
+    /// the API if the request has no date range or the chunk isn't positive.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_query_analytics_chunked(
+        &self,
+        host_id: &str,
+        request: &QueryAnalyticsRequest,
+        chunk: chrono::Duration,
+    ) -> Result<QueryAnalyticsResponse> {
+        request.validate()?;
+        let (Some(date_from), Some(date_to)) = (request.date_from, request.date_to) else {
+            return Err(YandexWebmasterError::InvalidRequest(
+                "date_from and date_to are required to split the range".to_string(),
+            ));
+        };
+        if chunk <= chrono::Duration::zero() {
+            return Err(YandexWebmasterError::InvalidRequest(
+                "chunk length must be positive".to_string(),
+            ));
+        }
+
+        let mut chunks = Vec::new();
+        let mut chunk_start = date_from;
+        loop {
+            let chunk_end = (chunk_start + chunk).min(date_to);
+            chunks.push((chunk_start, chunk_end));
+            if chunk_end >= date_to {
+                break;
+            }
+            chunk_start = chunk_end;
+        }
+
+        let responses: Vec<Option<QueryAnalyticsResponse>> = stream::iter(chunks)
+            .map(|(chunk_start, chunk_end)| async move {
+                self.ensure_rate_limit_available()?;
+                let request = QueryAnalyticsRequest {
+                    date_from: Some(chunk_start),
+                    date_to: Some(chunk_end),
+                    ..request.clone()
+                };
+                match self.get_query_analytics(host_id, &request).await {
+                    Ok(response) => Ok(Some(response)),
+                    Err(e) if e.error_code() == Some(&YandexErrorCode::SomeDatesAreUnavailable) => {
+                        tracing::debug!(
+                            date_from = %chunk_start,
+                            date_to = %chunk_end,
+                            "Skipping unavailable query analytics chunk"
+                        );
+                        Ok(None)
+                    }
+                    Err(e) => Err(e),
+                }
+            })
+            .buffered(BULK_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        Ok(QueryAnalyticsResponse::merge(
+            responses.into_iter().flatten().collect(),
+        ))
+    }
+
     /// Get statistics for a specific query
-    #[instrument(skip(self))]
+    ///
+    /// Fails with [`YandexWebmasterError::InvalidRequest`] without calling the API if
+    /// `query_indicator` is empty.
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_query_history(
         &self,
         host_id: &str,
         query_id: &str,
         request: &QueryHistoryRequest,
     ) -> Result<QueryHistoryResponse> {
+        request.validate()?;
+
         let url = format!(
-            "{}/user/{}/hosts/{}/search-queries/{}/history?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
-            query_id,
+            "{}/search-queries/{}/history?{}",
+            self.host_url(host_id).await?,
+            path_segment(query_id),
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
+    /// Get statistics for several queries as one long-format table
+    ///
+    /// Histories are fetched concurrently (at most 4 requests at a time) and flattened
+    /// into one row per query, date and indicator, ordered by the position of the query
+    /// in `query_ids`, then by date. Fails on the first failed request.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_query_histories_table(
+        &self,
+        host_id: &str,
+        query_ids: &[&str],
+        request: &QueryHistoryRequest,
+    ) -> Result<Vec<QueryHistoryRow>> {
+        request.validate()?;
+
+        let responses: Vec<QueryHistoryResponse> = stream::iter(query_ids)
+            .map(|query_id| async move {
+                self.ensure_rate_limit_available()?;
+                self.get_query_history(host_id, query_id, request).await
+            })
+            .buffered(BULK_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        Ok(responses.iter().flat_map(|r| r.rows()).collect())
+    }
+
     // ============================================================================
     // Sitemaps
     // ============================================================================
 
     /// Get list of all sitemap files
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_sitemaps(
         &self,
         host_id: &str,
         request: &GetSitemapsRequest,
     ) -> Result<SitemapsResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/sitemaps?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/sitemaps?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
     /// Get details of a specific sitemap
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_sitemap(&self, host_id: &str, sitemap_id: &str) -> Result<SitemapInfo> {
         let url = format!(
-            "{}/user/{}/hosts/{}/sitemaps/{}",
-            API_BASE_URL, self.user_id, host_id, sitemap_id
+            "{}/sitemaps/{}",
+            self.host_url(host_id).await?,
+            path_segment(sitemap_id)
         );
         self.get(&url).await
     }
 
+    /// Get details of a specific sitemap, returning `None` if it doesn't exist
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_sitemap_opt(
+        &self,
+        host_id: &str,
+        sitemap_id: &str,
+    ) -> Result<Option<SitemapInfo>> {
+        Self::not_found_to_none(self.get_sitemap(host_id, sitemap_id).await)
+    }
+
+    /// Get details of several sitemaps
+    ///
+    /// Sitemaps are fetched concurrently (at most 4 requests at a time) and the result
+    /// for every ID is returned in the input order. Once the client is
+    /// [rate limited](Self::check_rate_limit), the remaining sitemaps fail with
+    /// `RateLimitExhausted` without sending requests.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_sitemaps_detailed(
+        &self,
+        host_id: &str,
+        sitemap_ids: &[&str],
+    ) -> Vec<(String, Result<SitemapInfo>)> {
+        stream::iter(sitemap_ids)
+            .map(|sitemap_id| async move {
+                let result = match self.ensure_rate_limit_available() {
+                    Ok(()) => self.get_sitemap(host_id, sitemap_id).await,
+                    Err(e) => Err(e),
+                };
+                (sitemap_id.to_string(), result)
+            })
+            .buffered(BULK_CONCURRENCY)
+            .collect()
+            .await
+    }
+
     /// Get list of user-submitted sitemaps
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_user_sitemaps(
         &self,
         host_id: &str,
         request: &GetUserSitemapsRequest,
     ) -> Result<UserSitemapsResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/user-added-sitemaps?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/user-added-sitemaps?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
+    /// Get all top-level sitemaps known to the robot
+    ///
+    /// Loads pages of 100 sitemaps until a short page is returned.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_all_sitemaps(&self, host_id: &str) -> Result<Vec<SitemapInfo>> {
+        const PAGE_SIZE: i32 = 100;
+
+        let mut sitemaps = Vec::new();
+        loop {
+            let from = sitemaps.last().map(|s: &SitemapInfo| s.sitemap_id.clone());
+            let request = GetSitemapsRequest::builder()
+                .limit(PAGE_SIZE)
+                .from(from)
+                .build();
+            let page = self.get_sitemaps(host_id, &request).await?;
+            let page_len = page.sitemaps.len();
+            sitemaps.extend(page.sitemaps);
+
+            if page_len < PAGE_SIZE as usize {
+                break;
+            }
+        }
+
+        Ok(sitemaps)
+    }
+
+    /// Get top-level sitemaps the robot hasn't accessed for longer than `older_than`
+    ///
+    /// Sitemaps that were never accessed are included too. Complements the
+    /// `NO_SITEMAP_MODIFICATIONS` problem of [`Self::get_diagnostics`] with the list of
+    /// sitemaps to check.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_stale_sitemaps(
+        &self,
+        host_id: &str,
+        older_than: chrono::Duration,
+    ) -> Result<Vec<SitemapInfo>> {
+        let threshold = Utc::now() - older_than;
+        let mut sitemaps = self.get_all_sitemaps(host_id).await?;
+        sitemaps.retain(|sitemap| {
+            sitemap
+                .last_access_date
+                .is_none_or(|last_access| last_access < threshold)
+        });
+        Ok(sitemaps)
+    }
+
+    /// Get all user-submitted sitemaps
+    ///
+    /// Loads pages of 100 sitemaps until `count` sitemaps are loaded or a short page is returned.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_all_user_sitemaps(&self, host_id: &str) -> Result<Vec<UserSitemapInfo>> {
+        const PAGE_SIZE: i32 = 100;
+
+        let mut user_sitemaps = Vec::new();
+        loop {
+            let request = GetUserSitemapsRequest::builder()
+                .offset(user_sitemaps.len() as i32)
+                .limit(PAGE_SIZE)
+                .build();
+            let page = self.get_user_sitemaps(host_id, &request).await?;
+            let page_len = page.sitemaps.len();
+            user_sitemaps.extend(page.sitemaps);
+
+            if page_len < PAGE_SIZE as usize || user_sitemaps.len() >= page.count as usize {
+                break;
+            }
+        }
+
+        Ok(user_sitemaps)
+    }
+
+    /// Get full information about sitemaps added by the user
+    ///
+    /// Cross-references user-added sitemaps with all sitemaps known to the robot
+    /// (matching by ID, then by URL), so the result contains error and URL counts.
+    /// Sitemaps that the robot hasn't processed yet are not included.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_user_sitemaps_info(&self, host_id: &str) -> Result<Vec<SitemapInfo>> {
+        let user_sitemaps = self.get_all_user_sitemaps(host_id).await?;
+        let sitemaps = self.get_all_sitemaps(host_id).await?;
+
+        let result = user_sitemaps
+            .iter()
+            .filter_map(|user| {
+                sitemaps
+                    .iter()
+                    .find(|s| s.sitemap_id == user.sitemap_id)
+                    .or_else(|| sitemaps.iter().find(|s| s.sitemap_url == user.sitemap_url))
+                    .cloned()
+            })
+            .collect();
+
+        Ok(result)
+    }
+
     /// Add a new sitemap file
-    #[instrument(skip(self))]
+    ///
+    /// The sitemap URL must have the same scheme, host and port as the site, otherwise
+    /// [`YandexWebmasterError::SitemapHostMismatch`] is returned without calling the API.
+    ///
+    /// If the client is built with `treat_already_added_as_success(true)`, a
+    /// `SITEMAP_ALREADY_ADDED` error is returned as success with the existing sitemap ID.
+    ///
+    /// The API has no endpoint to make the robot re-read an added sitemap: added files
+    /// are recrawled on the robot's schedule, and re-adding an existing one only returns
+    /// `SITEMAP_ALREADY_ADDED`.
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn add_sitemap(&self, host_id: &str, url: &str) -> Result<AddSitemapResponse> {
+        Self::check_sitemap_host(host_id, url)?;
+        self.post_sitemap(host_id, url, self.treat_already_added_as_success)
+            .await
+    }
+
+    /// Add several sitemap files
+    ///
+    /// Every URL is checked to have the scheme, host and port of the site, and mismatching
+    /// ones fail with [`YandexWebmasterError::SitemapHostMismatch`] without calling the
+    /// API. The rest are added concurrently (at most 4 requests at a time). A `SITEMAP_ALREADY_ADDED`
+    /// error counts as success with the existing sitemap ID regardless of the
+    /// `treat_already_added_as_success` setting. Results are returned in the input order.
+    /// Once the client is [rate limited](Self::check_rate_limit), the remaining sitemaps
+    /// fail with `RateLimitExhausted` without sending requests.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn add_sitemaps(
+        &self,
+        host_id: &str,
+        urls: &[&str],
+    ) -> Vec<(String, Result<AddSitemapResponse>)> {
+        stream::iter(urls)
+            .map(|url| async move {
+                let result = async {
+                    Self::check_sitemap_host(host_id, url)?;
+                    self.ensure_rate_limit_available()?;
+                    self.post_sitemap(host_id, url, true).await
+                };
+                (url.to_string(), result.await)
+            })
+            .buffered(BULK_CONCURRENCY)
+            .collect()
+            .await
+    }
+
+    /// Checks that the sitemap URL has the scheme, host and port of the site
+    ///
+    /// The site URL is built from the host ID, so no request is made.
+    fn check_sitemap_host(host_id: &str, sitemap_url: &str) -> Result<()> {
+        let host_url = ParsedHostId::parse(host_id)?.url();
+        if !Self::is_same_host(&host_url, sitemap_url) {
+            return Err(YandexWebmasterError::SitemapHostMismatch {
+                sitemap_url: sitemap_url.to_string(),
+                host_url,
+            });
+        }
+        Ok(())
+    }
+
+    /// Sends the add sitemap request without checking the host of the URL
+    async fn post_sitemap(
+        &self,
+        host_id: &str,
+        url: &str,
+        already_added_as_success: bool,
+    ) -> Result<AddSitemapResponse> {
         let body = json!({ "url": url.to_string() });
-        let url = format!(
-            "{}/user/{}/hosts/{}/user-added-sitemaps",
-            API_BASE_URL, self.user_id, host_id
-        );
-        self.post(&url, &body).await
+        let url = format!("{}/user-added-sitemaps", self.host_url(host_id).await?);
+
+        let result = self.post(&url, &body).await;
+        let existing = match &result {
+            Err(YandexWebmasterError::ApiError { response, .. })
+                if response.error_code == YandexErrorCode::SitemapAlreadyAdded =>
+            {
+                response.sitemap_id.clone()
+            }
+            _ => None,
+        };
+        match existing {
+            Some(sitemap_id) if already_added_as_success => {
+                tracing::debug!(sitemap_id = %sitemap_id, "Sitemap is already added");
+                Ok(AddSitemapResponse { sitemap_id })
+            }
+            _ => result,
+        }
     }
 
     /// Get user-submitted sitemap details
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_user_sitemap(
         &self,
         host_id: &str,
         sitemap_id: &str,
     ) -> Result<UserSitemapInfo> {
         let url = format!(
-            "{}/user/{}/hosts/{}/user-added-sitemaps/{}",
-            API_BASE_URL, self.user_id, host_id, sitemap_id
+            "{}/user-added-sitemaps/{}",
+            self.host_url(host_id).await?,
+            path_segment(sitemap_id)
         );
         self.get(&url).await
     }
 
     /// Delete a user-submitted sitemap
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn delete_sitemap(&self, host_id: &str, sitemap_id: &str) -> Result<()> {
         let url = format!(
-            "{}/user/{}/hosts/{}/user-added-sitemaps/{}",
-            API_BASE_URL, self.user_id, host_id, sitemap_id
+            "{}/user-added-sitemaps/{}",
+            self.host_url(host_id).await?,
+            path_segment(sitemap_id)
         );
         self.delete(&url).await
     }
 
+    /// Delete several user-submitted sitemaps
+    ///
+    /// Sitemaps are deleted concurrently (at most 4 requests at a time) and the result
+    /// for every ID is returned in the input order. A `SITEMAP_NOT_FOUND` error counts
+    /// as success since the sitemap is already gone. Once the client is
+    /// [rate limited](Self::check_rate_limit), the remaining sitemaps fail with
+    /// `RateLimitExhausted` without sending requests.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn delete_sitemaps(
+        &self,
+        host_id: &str,
+        sitemap_ids: &[&str],
+    ) -> Vec<(String, Result<()>)> {
+        stream::iter(sitemap_ids)
+            .map(|sitemap_id| async move {
+                let result = match self.ensure_rate_limit_available() {
+                    Ok(()) => self.delete_sitemap(host_id, sitemap_id).await,
+                    Err(e) => Err(e),
+                };
+                let result = match result {
+                    Err(e) if e.error_code() == Some(&YandexErrorCode::SitemapNotFound) => {
+                        tracing::debug!(sitemap_id = %sitemap_id, "Sitemap is already deleted");
+                        Ok(())
+                    }
+                    result => result,
+                };
+                (sitemap_id.to_string(), result)
+            })
+            .buffered(BULK_CONCURRENCY)
+            .collect()
+            .await
+    }
+
     // ============================================================================
     // Indexing
     // ============================================================================
 
     /// Get indexing history
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_indexing_history(
         &self,
         host_id: &str,
         request: &IndexingHistoryRequest,
     ) -> Result<IndexingHistoryResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/indexing/history?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/indexing/history?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
     /// Get sample indexed pages
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_indexing_samples(
         &self,
         host_id: &str,
         request: &GetIndexingSamplesRequest,
     ) -> Result<IndexingSamplesResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/indexing/samples?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/indexing/samples?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
     /// Get pages in search history
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_search_urls_history(
         &self,
         host_id: &str,
         request: &IndexingHistoryRequest,
     ) -> Result<SearchUrlsHistoryResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/search-urls/in-search/history?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/search-urls/in-search/history?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
     /// Get sample pages in search
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_search_urls_samples(
         &self,
         host_id: &str,
         request: &GetSearchUrlsSamplesRequest,
     ) -> Result<SearchUrlsSamplesResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/search-urls/in-search/samples?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/search-urls/in-search/samples?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
+    /// Get all sample pages in search, paging through the whole list
+    ///
+    /// `progress` is called after every page with the number of samples fetched so far
+    /// and the total number reported by the API. The total may change between pages;
+    /// paging stops at the first short or empty page.
+    #[instrument(skip(self, progress), parent = self.span_parent())]
+    pub async fn get_all_search_urls_samples(
+        &self,
+        host_id: &str,
+        mut progress: impl FnMut(usize, i32),
+    ) -> Result<Vec<SearchUrlsSample>> {
+        const PAGE_SIZE: i32 = 100;
+
+        let mut samples = Vec::new();
+
+        loop {
+            let request = GetSearchUrlsSamplesRequest::builder()
+                .offset(samples.len() as i32)
+                .limit(PAGE_SIZE)
+                .build();
+            let page = self.get_search_urls_samples(host_id, &request).await?;
+            let page_len = page.samples.len();
+
+            samples.extend(page.samples);
+            progress(samples.len(), page.count);
+
+            if page_len < PAGE_SIZE as usize || samples.len() >= page.count as usize {
+                break;
+            }
+        }
+
+        Ok(samples)
+    }
+
     /// Get page appearance/removal history
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_search_events_history(
         &self,
         host_id: &str,
         request: &IndexingHistoryRequest,
     ) -> Result<SearchEventsHistoryResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/search-urls/events/history?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/search-urls/events/history?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
     /// Get sample page changes
-    #[instrument(skip(self))]
+    ///
+    /// Set `request.event` to receive only appearances or only removals.
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_search_events_samples(
         &self,
         host_id: &str,
         request: &GetSearchEventsSamplesRequest,
     ) -> Result<SearchEventsSamplesResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/search-urls/events/samples?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/search-urls/events/samples?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
+    /// Stream all sample page changes, paging through the whole list
+    ///
+    /// Pages are requested lazily as the stream is consumed. `event` filters the
+    /// samples on the server side. The stream ends at the first short or empty page
+    /// and stops after the first error.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # async fn example(client: yandex_webmaster_api::YandexWebmasterClient) -> yandex_webmaster_api::Result<()> {
+    /// use futures::TryStreamExt;
+    /// use yandex_webmaster_api::ApiSearchEventEnum;
+    ///
+    /// let removed: Vec<_> = client
+    ///     .get_search_events_samples_stream(
+    ///         "https:example.com:443",
+    ///         Some(ApiSearchEventEnum::RemovedFromSearch),
+    ///     )
+    ///     .try_collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get_search_events_samples_stream<'a>(
+        &'a self,
+        host_id: &'a str,
+        event: Option<ApiSearchEventEnum>,
+    ) -> impl Stream<Item = Result<SearchEventsSample>> + 'a {
+        const PAGE_SIZE: i32 = 100;
+
+        stream::try_unfold(Some(0), move |offset| async move {
+            let Some(offset) = offset else {
+                return Ok::<_, YandexWebmasterError>(None);
+            };
+            let request = GetSearchEventsSamplesRequest::builder()
+                .offset(offset)
+                .limit(PAGE_SIZE)
+                .event(event)
+                .build();
+            let page = self.get_search_events_samples(host_id, &request).await?;
+
+            let fetched = offset + page.samples.len() as i32;
+            let next_offset = (page.samples.len() == PAGE_SIZE as usize && fetched < page.count)
+                .then_some(fetched);
+            Ok(Some((
+                stream::iter(page.samples.into_iter().map(Result::Ok)),
+                next_offset,
+            )))
+        })
+        .try_flatten()
+    }
+
+    /// Get the sample pages that appeared in search since the date
+    ///
+    /// Pages through all [`ApiSearchEventEnum::AppearedInSearch`] samples and keeps
+    /// those with `event_date` at or after `since`. The API doesn't document the order
+    /// of the samples, so paging can't stop at the first older sample and every page is
+    /// read.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_pages_appeared_since(
+        &self,
+        host_id: &str,
+        since: DateTime<Utc>,
+    ) -> Result<Vec<SearchEventsSample>> {
+        self.get_search_events_samples_stream(host_id, Some(ApiSearchEventEnum::AppearedInSearch))
+            .try_filter(|sample| {
+                future::ready(
+                    sample.event == ApiSearchEventEnum::AppearedInSearch
+                        && sample.event_date >= since,
+                )
+            })
+            .try_collect()
+            .await
+    }
+
     // ============================================================================
     // Important URLs
     // ============================================================================
 
     /// Get list of important URLs
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_important_urls(&self, host_id: &str) -> Result<ImportantUrlsResponse> {
-        let url = format!(
-            "{}/user/{}/hosts/{}/important-urls",
-            API_BASE_URL, self.user_id, host_id
-        );
+        let url = format!("{}/important-urls", self.host_url(host_id).await?);
         self.get(&url).await
     }
 
+    /// Get the current status of a single important URL
+    ///
+    /// The API has no single-URL endpoint, so the URL is looked up in
+    /// [`Self::get_important_urls`]. Returns `None` if the URL isn't monitored.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_important_url(
+        &self,
+        host_id: &str,
+        url_param: &str,
+    ) -> Result<Option<ImportantUrl>> {
+        let response = self.get_important_urls(host_id).await?;
+        Ok(response.urls.into_iter().find(|url| url.url == url_param))
+    }
+
     /// Get important URLs history
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_important_urls_history(
         &self,
         host_id: &str,
         url_param: &str,
     ) -> Result<ImportantUrlHistoryResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/important-urls/history?url={}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/important-urls/history?url={}",
+            self.host_url(host_id).await?,
             urlencoding::encode(url_param)
         );
         self.get(&url).await
     }
 
+    /// Get the latest indexing status of a single URL
+    ///
+    /// The API has no per-URL indexing endpoint, so the status is taken from the most
+    /// recent entry of [`Self::get_important_urls_history`]. The URL must be in the
+    /// site's important URLs list.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_url_indexing_status(
+        &self,
+        host_id: &str,
+        url_param: &str,
+    ) -> Result<IndexingStatus> {
+        let history = self.get_important_urls_history(host_id, url_param).await?;
+
+        history
+            .history
+            .into_iter()
+            .filter_map(|entry| entry.indexing_status)
+            .max_by_key(|status| status.access_date)
+            .ok_or_else(|| YandexWebmasterError::IndexingStatusUnavailable(url_param.to_string()))
+    }
+
     // ============================================================================
     // Recrawl Management
     // ============================================================================
 
     /// Request page recrawl
-    #[instrument(skip(self))]
+    ///
+    /// Submitted tasks can't be withdrawn: the API has no endpoint to cancel a recrawl
+    /// task, and every submission counts against the daily quota.
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn recrawl_urls(&self, host_id: &str, url: &str) -> Result<RecrawlResponse> {
         let body = json!({ "url": url });
-        let url = format!(
-            "{}/user/{}/hosts/{}/recrawl/queue",
-            API_BASE_URL, self.user_id, host_id
-        );
+        let url = format!("{}/recrawl/queue", self.host_url(host_id).await?);
         self.post(&url, &body).await
     }
 
     /// Get list of recrawl tasks
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_recrawl_tasks(
         &self,
         host_id: &str,
         request: &GetRecrawlTasksRequest,
     ) -> Result<RecrawlTasksResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/recrawl/queue?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/recrawl/queue?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
+    /// Get all recrawl tasks that are still in progress
+    ///
+    /// Loads pages of 100 tasks from the default date range until a short page is returned.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_pending_recrawl_tasks(&self, host_id: &str) -> Result<Vec<RecrawlTask>> {
+        const PAGE_SIZE: i32 = 100;
+
+        let mut pending = Vec::new();
+        let mut offset = 0;
+        loop {
+            let request = GetRecrawlTasksRequest::builder()
+                .offset(offset)
+                .limit(PAGE_SIZE)
+                .build();
+            let page = self.get_recrawl_tasks(host_id, &request).await?;
+            let page_len = page.tasks.len();
+            offset += page_len as i32;
+            pending.extend(
+                page.tasks
+                    .into_iter()
+                    .filter(|task| task.state == RecrawlTaskState::InProgress),
+            );
+
+            if page_len < PAGE_SIZE as usize {
+                break;
+            }
+        }
+
+        Ok(pending)
+    }
+
     /// Get recrawl task status
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_recrawl_task(&self, host_id: &str, task_id: &str) -> Result<RecrawlTask> {
         let url = format!(
-            "{}/user/{}/hosts/{}/recrawl/queue/{}",
-            API_BASE_URL, self.user_id, host_id, task_id
+            "{}/recrawl/queue/{}",
+            self.host_url(host_id).await?,
+            path_segment(task_id)
         );
         self.get(&url).await
     }
 
+    /// Wait until all recrawl tasks of a batch are done or failed
+    ///
+    /// Every `poll_interval` the queue is listed in pages of 100 until all unfinished
+    /// tasks are found; tasks missing from the listing (e.g. older than its default date
+    /// range) are requested one by one. Once `timeout` passes, the last known state of
+    /// every task is returned, so tasks still [`RecrawlTaskState::InProgress`] indicate
+    /// a timeout. Tasks are returned in the order of `task_ids`.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn wait_for_recrawl_batch(
+        &self,
+        host_id: &str,
+        task_ids: &[&str],
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<Vec<RecrawlTask>> {
+        const PAGE_SIZE: i32 = 100;
+
+        let client = self.without_response_cache();
+        let deadline = Instant::now() + timeout;
+        let mut tasks: HashMap<String, RecrawlTask> = HashMap::new();
+
+        loop {
+            let mut pending: Vec<&str> = task_ids
+                .iter()
+                .copied()
+                .filter(|id| {
+                    tasks
+                        .get(*id)
+                        .is_none_or(|task| task.state == RecrawlTaskState::InProgress)
+                })
+                .collect();
+            if pending.is_empty() {
+                break;
+            }
+
+            let mut offset = 0;
+            while !pending.is_empty() {
+                let request = GetRecrawlTasksRequest::builder()
+                    .offset(offset)
+                    .limit(PAGE_SIZE)
+                    .build();
+                let page = client.get_recrawl_tasks(host_id, &request).await?;
+                let page_len = page.tasks.len();
+                offset += page_len as i32;
+
+                for task in page.tasks {
+                    if let Some(index) = pending.iter().position(|id| *id == task.task_id) {
+                        pending.swap_remove(index);
+                        tasks.insert(task.task_id.clone(), task);
+                    }
+                }
+                if page_len < PAGE_SIZE as usize {
+                    break;
+                }
+            }
+            for task_id in pending {
+                let task = client.get_recrawl_task(host_id, task_id).await?;
+                tasks.insert(task_id.to_string(), task);
+            }
+
+            let finished = tasks
+                .values()
+                .all(|task| task.state != RecrawlTaskState::InProgress);
+            if finished || Instant::now() + poll_interval > deadline {
+                break;
+            }
+            tracing::debug!(host_id = %host_id, "Waiting for recrawl tasks");
+            tokio::time::sleep(poll_interval).await;
+        }
+
+        Ok(task_ids.iter().filter_map(|id| tasks.remove(*id)).collect())
+    }
+
+    /// Get recrawl task status, returning `None` if the task doesn't exist
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_recrawl_task_opt(
+        &self,
+        host_id: &str,
+        task_id: &str,
+    ) -> Result<Option<RecrawlTask>> {
+        Self::not_found_to_none(self.get_recrawl_task(host_id, task_id).await)
+    }
+
     /// Get recrawl quota
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_recrawl_quota(&self, host_id: &str) -> Result<RecrawlQuotaResponse> {
-        let url = format!(
-            "{}/user/{}/hosts/{}/recrawl/quota",
-            API_BASE_URL, self.user_id, host_id
-        );
+        let url = format!("{}/recrawl/quota", self.host_url(host_id).await?);
         self.get(&url).await
     }
 
+    // ============================================================================
+    // IndexNow
+    // ============================================================================
+
+    /// Submit URLs of the site to Yandex via IndexNow
+    ///
+    /// IndexNow is a separate protocol rather than part of the Webmaster API: the request
+    /// goes to the [IndexNow endpoint](YandexWebmasterClientBuilder::index_now_url)
+    /// without the OAuth token and is authorized by the
+    /// [key](YandexWebmasterClientBuilder::index_now_key) published on the site. Unlike
+    /// [`Self::recrawl_urls`], up to 10,000 URLs are sent in one request, submissions
+    /// don't count against the recrawl quota and no tasks are created, so the
+    /// crawling can't be tracked. The site doesn't have to be verified.
+    ///
+    /// All URLs must be on the host of `host_id`. The endpoint only acknowledges the
+    /// submission; an invalid key is reported with a 403 status and URLs not matching
+    /// the key with 422, and they may be checked after the response, so a successful
+    /// result doesn't mean the URLs will be crawled.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`YandexWebmasterError::InvalidRequest`] if no key is configured, the key
+    /// has invalid characters, more than 10,000 URLs are passed or a URL differs from the
+    /// site in scheme, host or port. Nothing is sent for an empty list.
+    #[instrument(skip(self, urls), fields(urls = urls.len()), parent = self.span_parent())]
+    pub async fn index_now(&self, host_id: &str, urls: &[&str]) -> Result<()> {
+        let Some(key) = &self.index_now_key else {
+            return Err(YandexWebmasterError::InvalidRequest(
+                "IndexNow key is not configured".to_string(),
+            ));
+        };
+        let valid_key = (8..=128).contains(&key.len())
+            && key.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-');
+        if !valid_key {
+            return Err(YandexWebmasterError::InvalidRequest(
+                "IndexNow key must be 8 to 128 characters of a-z, A-Z, 0-9 and -".to_string(),
+            ));
+        }
+        if urls.len() > INDEX_NOW_MAX_URLS {
+            return Err(YandexWebmasterError::InvalidRequest(format!(
+                "IndexNow accepts at most {} URLs, got {}",
+                INDEX_NOW_MAX_URLS,
+                urls.len()
+            )));
+        }
+        if urls.is_empty() {
+            return Ok(());
+        }
+
+        let host_id = ParsedHostId::parse(host_id)?;
+        let host_url = host_id.url();
+        if let Some(url) = urls.iter().find(|url| !Self::is_same_host(&host_url, url)) {
+            return Err(YandexWebmasterError::InvalidRequest(format!(
+                "URL {} is not on host {}",
+                url, host_url
+            )));
+        }
+
+        let mut body = json!({
+            "host": host_id.host,
+            "key": key,
+            "urlList": urls,
+        });
+        if let Some(key_location) = &self.index_now_key_location {
+            body["keyLocation"] = json!(key_location);
+        }
+
+        tracing::debug!(url = %self.index_now_url, "Making IndexNow request");
+        let (request, request_id) = self.request(reqwest::Method::POST, &self.index_now_url);
+        // IndexNow responses don't affect the rate limit state of the API
+        let response = request
+            .with_extension(Unauthenticated)
+            .header(reqwest::header::CONTENT_TYPE, JSON_CONTENT_TYPE)
+            .body(serde_json::to_vec(&body)?)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(Self::parse_error(response, request_id).await);
+        }
+
+        Ok(())
+    }
+
     // ============================================================================
     // Links
     // ============================================================================
 
     /// Get broken internal links samples
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_broken_links(
         &self,
         host_id: &str,
         request: &BrokenLinksRequest,
     ) -> Result<BrokenLinksResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/links/internal/broken/samples?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/links/internal/broken/samples?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
     /// Get broken links history
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_broken_links_history(
         &self,
         host_id: &str,
         request: &BrokenLinkHistoryRequest,
     ) -> Result<BrokenLinkHistoryResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/links/internal/broken/history?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/links/internal/broken/history?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
     /// Get external backlinks samples
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_external_links(
         &self,
         host_id: &str,
         request: &ExternalLinksRequest,
     ) -> Result<ExternalLinksResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/links/external/samples?{}",
-            API_BASE_URL,
-            self.user_id,
-            host_id,
+            "{}/links/external/samples?{}",
+            self.host_url(host_id).await?,
             self.qs.serialize_string(request)?
         );
         self.get(&url).await
     }
 
     /// Get backlinks history
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_external_links_history(
         &self,
         host_id: &str,
     ) -> Result<ExternalLinksHistoryResponse> {
         let url = format!(
-            "{}/user/{}/hosts/{}/links/external/history?indicator=LINKS_TOTAL_COUNT",
-            API_BASE_URL, self.user_id, host_id
+            "{}/links/external/history?indicator=LINKS_TOTAL_COUNT",
+            self.host_url(host_id).await?
         );
         self.get(&url).await
     }
@@ -633,27 +1982,195 @@ impl YandexWebmasterClient {
     // ============================================================================
 
     /// Get site diagnostic report
-    #[instrument(skip(self))]
+    #[instrument(skip(self), parent = self.span_parent())]
     pub async fn get_diagnostics(&self, host_id: &str) -> Result<DiagnosticsResponse> {
+        let url = format!("{}/diagnostics", self.host_url(host_id).await?);
+        self.get(&url).await
+    }
+
+    /// Wait until the diagnostics no longer report the problem
+    ///
+    /// Polls [`Self::get_diagnostics`] every `poll_interval` until the state of the
+    /// problem is [`ApiSiteProblemState::Absent`] or the problem is missing from the
+    /// report. Returns `false` if the problem is still reported once `timeout` passes.
+    /// Changes of `last_state_update` are logged, so a problem re-detected after a fix
+    /// can be told from one Yandex hasn't rechecked yet.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn wait_for_problem_resolved(
+        &self,
+        host_id: &str,
+        problem: ApiSiteProblemTypeEnum,
+        poll_interval: Duration,
+        timeout: Duration,
+    ) -> Result<bool> {
+        let client = self.without_response_cache();
+        let deadline = Instant::now() + timeout;
+        let mut last_update = None;
+
+        loop {
+            let diagnostics = client.get_diagnostics(host_id).await?;
+            let Some(info) = diagnostics.problems.get(&problem) else {
+                return Ok(true);
+            };
+            if info.state == ApiSiteProblemState::Absent {
+                return Ok(true);
+            }
+
+            if last_update.is_some_and(|update| update != info.last_state_update) {
+                tracing::debug!(
+                    host_id = %host_id,
+                    state = ?info.state,
+                    last_state_update = ?info.last_state_update,
+                    "Problem was rechecked but is still reported"
+                );
+            }
+            last_update = Some(info.last_state_update);
+
+            if Instant::now() + poll_interval > deadline {
+                return Ok(false);
+            }
+            tracing::debug!(host_id = %host_id, "Waiting for the problem to be resolved");
+            tokio::time::sleep(poll_interval).await;
+        }
+    }
+
+    // ============================================================================
+    // Feeds
+    // ============================================================================
+
+    /// Get list of feeds added to the site
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_feeds(&self, host_id: &str) -> Result<FeedsResponse> {
+        let url = format!("{}/feeds/list", self.host_url(host_id).await?);
+        self.get(&url).await
+    }
+
+    /// Get information about a feed by its URL
+    ///
+    /// Returns `None` if the feed isn't added to the site.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_feed(&self, host_id: &str, feed_url: &str) -> Result<Option<FeedInfo>> {
+        let feeds = self.get_feeds(host_id).await?;
+        Ok(feeds.feeds.into_iter().find(|feed| feed.url == feed_url))
+    }
+
+    /// Start adding a feed to the site
+    ///
+    /// The feed is checked asynchronously, use [`Self::get_feed_add_status`] with the
+    /// returned request ID to get the result. Errors returned right away:
+    /// - `FEEDS_LIMIT_EXCEEDED` - the site already has the maximum number of feeds
+    /// - `FEEDS_CATEGORY_BAN` - feeds of this type are banned for the site
+    /// - `FEED_ALREADY_ADDED` - the feed is already added, see
+    ///   [`YandexWebmasterError::is_already_added`]
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn add_feed(
+        &self,
+        host_id: &str,
+        request: &AddFeedRequest,
+    ) -> Result<AddFeedResponse> {
+        let url = format!("{}/feeds/add/start", self.host_url(host_id).await?);
+        let body = json!({ "feed": request });
+        self.post(&url, &body).await
+    }
+
+    /// Get the result of a feed add request
+    ///
+    /// Check failures such as an unreachable feed URL are reported as [`FeedStatus`]
+    /// variants rather than errors.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn get_feed_add_status(
+        &self,
+        host_id: &str,
+        request_id: &str,
+    ) -> Result<FeedAddInfo> {
         let url = format!(
-            "{}/user/{}/hosts/{}/diagnostics",
-            API_BASE_URL, self.user_id, host_id
+            "{}/feeds/add/info?requestId={}",
+            self.host_url(host_id).await?,
+            urlencoding::encode(request_id)
         );
         self.get(&url).await
     }
 
+    /// Delete a feed from the site
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn delete_feed(&self, host_id: &str, feed_url: &str) -> Result<()> {
+        self.delete_feeds(host_id, &[feed_url]).await
+    }
+
+    /// Delete several feeds from the site
+    ///
+    /// With a [body size limit](YandexWebmasterClientBuilder::max_body_size) the URLs
+    /// are split into requests fitting it and sent one after another, so feeds of the
+    /// requests before a failed one are already deleted.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn delete_feeds(&self, host_id: &str, feed_urls: &[&str]) -> Result<()> {
+        let url = format!("{}/feeds/batch/remove", self.host_url(host_id).await?);
+        for chunk in self.chunk_by_body_size(feed_urls, |urls| json!({ "urls": urls }))? {
+            let body = json!({ "urls": chunk });
+            self.post_ignoring_response(&url, &body).await?;
+        }
+        Ok(())
+    }
+
     // ============================================================================
     // Helper Methods
     // ============================================================================
 
-    /// Generic GET request helper
+    /// Generic GET request helper, using the response cache if it is configured
     #[instrument(skip(self))]
     async fn get<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
+        let Some((policy, ttl)) = self
+            .response_cache
+            .as_ref()
+            .and_then(|policy| Some((policy, policy.ttl(url)?)))
+        else {
+            return self.get_uncached(url).await;
+        };
+
+        if let Some(body) = policy.cache.get(url).await {
+            tracing::debug!(url = %url, "Using cached response");
+            return self.parse_body(url, &body);
+        }
+
+        tracing::debug!(url = %url, "Making GET request");
+        let (request, request_id) = self.request(reqwest::Method::GET, url);
+        let response = self.send(request).await?;
+        let body = Self::response_body(response, request_id).await?;
+        let result = self.parse_body(url, &body)?;
+        policy.cache.put(url, body, ttl).await;
+        Ok(result)
+    }
+
+    /// GET request helper bypassing the response cache
+    async fn get_uncached<T: serde::de::DeserializeOwned>(&self, url: &str) -> Result<T> {
         tracing::debug!(url = %url, "Making GET request");
+        let (request, request_id) = self.request(reqwest::Method::GET, url);
+        let response = self.send(request).await?;
+        self.handle_response(url, response, request_id).await
+    }
 
-        let response = self.client.get(url).send().await?;
+    /// Removes the cached responses that a successful mutating request to the URL may change
+    ///
+    /// Requests to the resources of a site invalidate the responses of the site. Adding,
+    /// deleting and verifying a site also change the list of sites, so they invalidate
+    /// the responses of all sites.
+    async fn invalidate_cached(&self, url: &str) {
+        let Some(policy) = &self.response_cache else {
+            return;
+        };
 
-        Self::handle_response(response).await
+        let path = url.split('?').next().unwrap_or(url);
+        let prefix = match path.split_once("/hosts/") {
+            Some((hosts, rest)) => match rest.split_once('/') {
+                Some((host, resource)) if resource != "verification" => {
+                    format!("{}/hosts/{}", hosts, host)
+                }
+                _ => format!("{}/hosts", hosts),
+            },
+            None => path.to_string(),
+        };
+        tracing::debug!(prefix = %prefix, "Invalidating cached responses");
+        policy.cache.invalidate(&prefix).await;
     }
 
     /// Generic POST request helper
@@ -663,19 +2180,152 @@ impl YandexWebmasterClient {
         url: &str,
         body: &B,
     ) -> Result<T> {
+        let json_body = serde_json::to_vec(body)?;
+        self.post_body(url, self.json_content_type(), json_body)
+            .await
+    }
+
+    /// POST request helper for endpoints whose response body isn't needed
+    ///
+    /// The body isn't parsed, so strict deserialization doesn't apply to it.
+    #[instrument(skip(self, body))]
+    async fn post_ignoring_response<B: serde::Serialize>(&self, url: &str, body: &B) -> Result<()> {
+        let json_body = serde_json::to_vec(body)?;
+        let (response, request_id) = self
+            .send_body(url, self.json_content_type(), json_body)
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(Self::parse_error(response, request_id).await);
+        }
+
+        Ok(())
+    }
+
+    /// Returns the content type of JSON request bodies
+    fn json_content_type(&self) -> &str {
+        self.content_type.as_deref().unwrap_or(JSON_CONTENT_TYPE)
+    }
+
+    /// POST request helper for a serialized body of the content type
+    async fn post_body<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<T> {
+        let (response, request_id) = self.send_body(url, content_type, body).await?;
+        self.handle_response(url, response, request_id).await
+    }
+
+    /// Sends a POST request with a serialized body of the content type
+    async fn send_body(
+        &self,
+        url: &str,
+        content_type: &str,
+        body: Vec<u8>,
+    ) -> Result<(reqwest::Response, Option<String>)> {
+        if let Some(limit) = self.max_body_size {
+            if body.len() > limit {
+                return Err(YandexWebmasterError::PayloadTooLarge {
+                    size: body.len(),
+                    limit,
+                });
+            }
+        }
+
         tracing::debug!(url = %url, "Making POST request");
 
-        let json_body = serde_json::to_string(body)?;
+        if let Some(compressed) = self.compress_body(&body) {
+            let (request, request_id) = self.request(reqwest::Method::POST, url);
+            let request = request
+                .header(reqwest::header::CONTENT_TYPE, content_type)
+                .header(reqwest::header::CONTENT_ENCODING, "gzip")
+                .body(compressed);
+            let response = self.send(request).await?;
 
-        let response = self
-            .client
-            .post(url)
-            .header("Content-Type", "application/json")
-            .body(json_body)
-            .send()
-            .await?;
+            if response.status() != reqwest::StatusCode::UNSUPPORTED_MEDIA_TYPE {
+                if response.status().is_success() {
+                    self.invalidate_cached(url).await;
+                }
+                return Ok((response, request_id));
+            }
+
+            tracing::warn!("Compressed request body rejected, disabling compression");
+            self.compression_rejected.store(true, Ordering::Relaxed);
+        }
 
-        Self::handle_response(response).await
+        let (request, request_id) = self.request(reqwest::Method::POST, url);
+        let request = request
+            .header(reqwest::header::CONTENT_TYPE, content_type)
+            .body(body);
+        let response = self.send(request).await?;
+        if response.status().is_success() {
+            self.invalidate_cached(url).await;
+        }
+
+        Ok((response, request_id))
+    }
+
+    /// Splits the items into chunks whose bodies built by `body` fit the body size limit
+    ///
+    /// Returns a single chunk without a limit, and fails with `PayloadTooLarge` if a
+    /// single item doesn't fit.
+    fn chunk_by_body_size<'a>(
+        &self,
+        items: &'a [&'a str],
+        body: impl Fn(&[&str]) -> serde_json::Value,
+    ) -> Result<Vec<&'a [&'a str]>> {
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        let Some(limit) = self.max_body_size else {
+            return Ok(vec![items]);
+        };
+
+        // Every item of a chunk adds its JSON string and a comma to the empty body
+        let empty_size = serde_json::to_vec(&body(&[]))?.len();
+        let mut chunks = Vec::new();
+        let mut start = 0;
+        let mut size = empty_size;
+        for (i, item) in items.iter().enumerate() {
+            let item_size = serde_json::to_vec(item)?.len();
+            if empty_size + item_size > limit {
+                return Err(YandexWebmasterError::PayloadTooLarge {
+                    size: empty_size + item_size,
+                    limit,
+                });
+            }
+
+            let added_size = item_size + usize::from(i > start);
+            if size + added_size > limit {
+                chunks.push(&items[start..i]);
+                start = i;
+                size = empty_size + item_size;
+            } else {
+                size += added_size;
+            }
+        }
+        chunks.push(&items[start..]);
+
+        Ok(chunks)
+    }
+
+    /// Compresses the request body with gzip if compression is enabled and the body is large enough
+    fn compress_body(&self, body: &[u8]) -> Option<Vec<u8>> {
+        let threshold = self.compression_threshold?;
+        if body.len() <= threshold || self.compression_rejected.load(Ordering::Relaxed) {
+            return None;
+        }
+
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        match encoder.write_all(body).and_then(|_| encoder.finish()) {
+            Ok(compressed) => Some(compressed),
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to compress request body");
+                None
+            }
+        }
     }
 
     /// Generic DELETE request helper
@@ -683,36 +2333,182 @@ impl YandexWebmasterClient {
     async fn delete(&self, url: &str) -> Result<()> {
         tracing::debug!(url = %url, "Making DELETE request");
 
-        let response = self.client.delete(url).send().await?;
+        let (request, request_id) = self.request(reqwest::Method::DELETE, url);
+        let response = self.send(request).await?;
 
         if !response.status().is_success() {
-            return Err(Self::parse_error(response).await);
+            return Err(Self::parse_error(response, request_id).await);
         }
+        self.invalidate_cached(url).await;
 
         Ok(())
     }
 
+    /// Creates a request with the request ID header if request IDs are enabled
+    fn request(
+        &self,
+        method: reqwest::Method,
+        url: &str,
+    ) -> (reqwest_middleware::RequestBuilder, Option<String>) {
+        let mut request = self.client.request(method, url);
+        if let Some(headers) = &self.extra_headers {
+            request = request.headers(HeaderMap::clone(headers));
+        }
+        let request_id = match &self.request_id {
+            Some(request_id) => Some(request_id.clone()),
+            None => self.generate_request_ids.then(Self::generate_request_id),
+        };
+
+        match request_id {
+            Some(request_id) => (
+                request.header(REQUEST_ID_HEADER, &request_id),
+                Some(request_id),
+            ),
+            None => (request, None),
+        }
+    }
+
+    /// Sends the request, waiting for a free slot if concurrent requests are limited
+    async fn send(&self, request: reqwest_middleware::RequestBuilder) -> Result<reqwest::Response> {
+        let _permit = match &self.request_limiter {
+            Some(limiter) => limiter.acquire().await.ok(),
+            None => None,
+        };
+
+        let response = request.send().await?;
+        if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            let delay = response
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse().ok())
+                .map_or(DEFAULT_RATE_LIMIT_DELAY, Duration::from_secs);
+            *self
+                .rate_limited_until
+                .lock()
+                .unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + delay);
+        }
+
+        Ok(response)
+    }
+
+    /// Fails with `RateLimitExhausted` if the client is rate limited
+    fn ensure_rate_limit_available(&self) -> Result<()> {
+        match self.check_rate_limit() {
+            RateLimitStatus::Available => Ok(()),
+            RateLimitStatus::Exhausted { retry_after } => {
+                Err(YandexWebmasterError::RateLimitExhausted { retry_after })
+            }
+        }
+    }
+
+    /// Generates a unique request ID
+    fn generate_request_id() -> String {
+        uuid::Uuid::new_v4().to_string()
+    }
+
+    /// Maps "not found" API errors to `Ok(None)`
+    fn not_found_to_none<T>(result: Result<T>) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if e.is_not_found() => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Checks that the URL has the same scheme, host and port as the site URL
+    ///
+    /// Default ports are normalised, so `https://example.com:443/` matches `https://example.com/`.
+    fn is_same_host(host_url: &str, url: &str) -> bool {
+        let host = reqwest::Url::parse(host_url);
+        let url = reqwest::Url::parse(url);
+
+        match (host, url) {
+            (Ok(host), Ok(url)) => {
+                host.scheme() == url.scheme()
+                    && host.host_str().is_some()
+                    && host.host_str() == url.host_str()
+                    && host.port_or_known_default() == url.port_or_known_default()
+            }
+            _ => false,
+        }
+    }
+
     /// Parse API error response
     #[instrument(skip(response))]
-    async fn parse_error(response: reqwest::Response) -> YandexWebmasterError {
+    async fn parse_error(
+        response: reqwest::Response,
+        request_id: Option<String>,
+    ) -> YandexWebmasterError {
         let status = response.status();
         let status_code = status.as_u16();
+        let request_id_suffix = request_id
+            .as_ref()
+            .map(|id| format!(", Request ID: {}", id))
+            .unwrap_or_default();
+        let allow: Vec<String> = response
+            .headers()
+            .get_all(reqwest::header::ALLOW)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|method| method.trim().to_string())
+            .filter(|method| !method.is_empty())
+            .collect();
 
         // Try to parse structured error response
         match response.text().await {
+            Ok(error_text) if status == reqwest::StatusCode::METHOD_NOT_ALLOWED => {
+                let error_code = serde_json::from_str::<YandexApiErrorResponse>(&error_text)
+                    .map(|api_error| api_error.error_code)
+                    .unwrap_or(YandexErrorCode::MethodNotAllowed);
+                tracing::error!(
+                    status = %status,
+                    allow = ?allow,
+                    request_id = ?request_id,
+                    "HTTP method not allowed by the endpoint"
+                );
+                YandexWebmasterError::MethodNotAllowed {
+                    allow,
+                    error_code,
+                    body: error_text,
+                    request_id,
+                }
+            }
             Ok(error_text) => {
                 // Try to parse as structured Yandex API error
                 match serde_json::from_str::<YandexApiErrorResponse>(&error_text) {
+                    Ok(api_error)
+                        if api_error.error_code == YandexErrorCode::ContentTypeUnsupported =>
+                    {
+                        tracing::error!(
+                            status = %status,
+                            acceptable_types = ?api_error.acceptable_types,
+                            request_id = ?request_id,
+                            "Request content type unsupported"
+                        );
+                        YandexWebmasterError::ContentTypeUnsupported {
+                            status: status_code,
+                            acceptable_types: api_error
+                                .acceptable_types
+                                .clone()
+                                .unwrap_or_default(),
+                            response: Box::new(api_error),
+                            request_id,
+                        }
+                    }
                     Ok(api_error) => {
                         tracing::error!(
                             status = %status,
                             error_code = %api_error.error_code,
                             error_message = %api_error.error_message,
+                            request_id = ?request_id,
                             "Structured API error"
                         );
                         YandexWebmasterError::ApiError {
                             status: status_code,
-                            response: api_error,
+                            response: Box::new(api_error),
+                            request_id,
                         }
                     }
                     Err(_) => {
@@ -720,12 +2516,15 @@ impl YandexWebmasterClient {
                         tracing::error!(
                             status = %status,
                             error = %error_text,
+                            request_id = ?request_id,
                             "API request failed with unstructured error"
                         );
-                        YandexWebmasterError::GenericApiError(format!(
-                            "Status: {}, Error: {}",
-                            status, error_text
-                        ))
+                        YandexWebmasterError::UnstructuredApiError {
+                            status: status_code,
+                            error_code: status.into(),
+                            body: error_text,
+                            request_id,
+                        }
                     }
                 }
             }
@@ -733,26 +2532,2076 @@ impl YandexWebmasterClient {
                 tracing::error!(
                     status = %status,
                     error = %e,
+                    request_id = ?request_id,
                     "Failed to read error response"
                 );
                 YandexWebmasterError::GenericApiError(format!(
-                    "Status: {}, Failed to read error response: {}",
-                    status, e
+                    "Status: {}, Failed to read error response: {}{}",
+                    status, e, request_id_suffix
                 ))
             }
         }
     }
 
     /// Handle API response
-    #[instrument(skip(response))]
+    #[instrument(skip(self, response))]
     async fn handle_response<T: serde::de::DeserializeOwned>(
+        &self,
+        url: &str,
         response: reqwest::Response,
+        request_id: Option<String>,
     ) -> Result<T> {
+        let body = Self::response_body(response, request_id).await?;
+        self.parse_body(url, &body)
+    }
+
+    /// Returns the body of a successful response or the parsed API error
+    async fn response_body(
+        response: reqwest::Response,
+        request_id: Option<String>,
+    ) -> Result<bytes::Bytes> {
         if !response.status().is_success() {
-            return Err(Self::parse_error(response).await);
+            return Err(Self::parse_error(response, request_id).await);
+        }
+
+        Ok(response.bytes().await?)
+    }
+
+    /// Parses a response body of the endpoint
+    fn parse_body<T: serde::de::DeserializeOwned>(&self, url: &str, body: &[u8]) -> Result<T> {
+        parse_response_from(url, body, self.strict_deserialization)
+    }
+}
+
+/// Percent-encodes an ID for use as a single URL path segment
+///
+/// Characters allowed in a segment by RFC 3986 are kept, so host IDs like
+/// `https:example.com:443` are sent as is, while `/`, `?`, `#`, `%` and non-ASCII
+/// characters can't change the meaning of the URL.
+fn path_segment(segment: &str) -> Cow<'_, str> {
+    let is_allowed = |b: u8| b.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=:@".contains(&b);
+    if segment.bytes().all(is_allowed) {
+        return Cow::Borrowed(segment);
+    }
+
+    let mut encoded = String::with_capacity(segment.len() * 3);
+    for b in segment.bytes() {
+        if is_allowed(b) {
+            encoded.push(b as char);
+        } else {
+            encoded.push_str(&format!("%{b:02X}"));
+        }
+    }
+    Cow::Owned(encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{MOCK_OAUTH_TOKEN, MOCK_USER_ID, SAMPLE_HOST_ID};
+    use tracing::Instrument;
+    use wiremock::{
+        matchers::{body_json, header, header_exists, method, path, path_regex, query_param},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    fn client(mut builder: YandexWebmasterClientBuilder) -> YandexWebmasterClient {
+        let client = YandexWebmasterClient::build_client(&mut builder).unwrap();
+        YandexWebmasterClient::from_parts(builder, client, Some(MOCK_USER_ID))
+    }
+
+    /// Subscriber recording the parents of spans and the messages of warnings
+    #[derive(Clone, Default)]
+    struct RecordingSubscriber(Arc<Mutex<Recording>>);
+
+    #[derive(Default)]
+    struct Recording {
+        spans: Vec<(&'static tracing::Metadata<'static>, Option<tracing::Id>)>,
+        entered: Vec<tracing::Id>,
+        warnings: Vec<String>,
+    }
+
+    impl RecordingSubscriber {
+        /// Returns the name of the parent of every span with the name
+        fn parents(&self, name: &str) -> Vec<Option<&'static str>> {
+            let recording = self.0.lock().unwrap();
+            let name_of = |id: &tracing::Id| recording.spans[id.into_u64() as usize - 1].0.name();
+            recording
+                .spans
+                .iter()
+                .filter(|(metadata, _)| metadata.name() == name)
+                .map(|(_, parent)| parent.as_ref().map(name_of))
+                .collect()
+        }
+
+        fn warnings(&self) -> Vec<String> {
+            self.0.lock().unwrap().warnings.clone()
+        }
+    }
+
+    impl tracing::Subscriber for RecordingSubscriber {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &tracing::span::Attributes<'_>) -> tracing::Id {
+            let mut recording = self.0.lock().unwrap();
+            let parent = if attrs.is_contextual() {
+                recording.entered.last().cloned()
+            } else {
+                attrs.parent().cloned()
+            };
+            recording.spans.push((attrs.metadata(), parent));
+            tracing::Id::from_u64(recording.spans.len() as u64)
+        }
+
+        fn record(&self, _: &tracing::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::Id, _: &tracing::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            struct Message(String);
+            impl tracing::field::Visit for Message {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    if field.name() == "message" {
+                        self.0 = format!("{value:?}");
+                    }
+                }
+            }
+
+            if *event.metadata().level() == tracing::Level::WARN {
+                let mut message = Message(String::new());
+                event.record(&mut message);
+                self.0.lock().unwrap().warnings.push(message.0);
+            }
+        }
+
+        fn enter(&self, span: &tracing::Id) {
+            self.0.lock().unwrap().entered.push(span.clone());
+        }
+
+        fn exit(&self, span: &tracing::Id) {
+            let mut recording = self.0.lock().unwrap();
+            if let Some(index) = recording.entered.iter().rposition(|id| id == span) {
+                recording.entered.remove(index);
+            }
+        }
+
+        fn current_span(&self) -> tracing_core::span::Current {
+            let recording = self.0.lock().unwrap();
+            match recording.entered.last() {
+                Some(id) => tracing_core::span::Current::new(
+                    id.clone(),
+                    recording.spans[id.into_u64() as usize - 1].0,
+                ),
+                None => tracing_core::span::Current::none(),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compressed_request_fallback() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("Content-Encoding", "gzip"))
+            .respond_with(ResponseTemplate::new(415).set_body_json(json!({
+                "error_code": "CONTENT_ENCODING_UNSUPPORTED",
+                "error_message": "some string"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "requestId": "1" })))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .compress_requests(true)
+                .compression_threshold(0),
+        );
+        let request = AddFeedRequest::builder()
+            .url("https://example.com/feed.xml")
+            .feed_type(FeedType::Goods)
+            .build();
+
+        for _ in 0..2 {
+            let response = client.add_feed(SAMPLE_HOST_ID, &request).await.unwrap();
+            assert_eq!(response.request_id, "1");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_small_request_not_compressed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header_exists("Content-Encoding"))
+            .respond_with(ResponseTemplate::new(500))
+            .expect(0)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "requestId": "1" })))
+            .mount(&server)
+            .await;
+
+        let client = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .compress_requests(true),
+        );
+        let request = AddFeedRequest::builder()
+            .url("https://example.com/feed.xml")
+            .feed_type(FeedType::Goods)
+            .build();
+
+        client.add_feed(SAMPLE_HOST_ID, &request).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_request_id_in_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("X-Request-Id", "import-42"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "error_code": "HOST_NOT_FOUND",
+                "error_message": "some string"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(header_exists("X-Request-Id"))
+            .respond_with(ResponseTemplate::new(500).set_body_string("Internal Server Error"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .request_id(true),
+        );
+
+        let error = client
+            .with_request_id("import-42".to_string())
+            .get_host(SAMPLE_HOST_ID)
+            .await
+            .unwrap_err();
+        assert_eq!(error.request_id(), Some("import-42"));
+
+        let error = client.get_host(SAMPLE_HOST_ID).await.unwrap_err();
+        assert!(error.request_id().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_with_headers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("X-Tenant-Id", "acme"))
+            .and(header("Baggage", "job=import"))
+            .and(header("Authorization", "OAuth test-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo::sample()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let mut tenant = HeaderMap::new();
+        tenant.insert("X-Tenant-Id", "acme".parse().unwrap());
+        tenant.insert(
+            reqwest::header::AUTHORIZATION,
+            "Bearer other".parse().unwrap(),
+        );
+        let mut baggage = HeaderMap::new();
+        baggage.insert("Baggage", "job=import".parse().unwrap());
+
+        client
+            .with_headers(tenant)
+            .with_headers(baggage)
+            .get_host(SAMPLE_HOST_ID)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_primary_verified_host() {
+        let server = MockServer::start().await;
+        let unverified = HostInfo {
+            host_id: "https:unverified.com:443".to_string(),
+            verified: false,
+            ..HostInfo::sample()
+        };
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostsResponse {
+                hosts: vec![unverified.clone(), HostInfo::sample()],
+            }))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostsResponse {
+                hosts: vec![unverified],
+            }))
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+
+        let host = client.get_primary_verified_host().await.unwrap();
+        assert_eq!(host, HostInfo::sample());
+
+        let error = client.get_primary_verified_host().await.unwrap_err();
+        assert!(matches!(error, YandexWebmasterError::NoVerifiedHosts));
+    }
+
+    #[tokio::test]
+    async fn test_check_https_migration() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/user/{}/hosts/http:example.com:80",
+                MOCK_USER_ID
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo {
+                host_id: "http:example.com:80".to_string(),
+                ascii_host_url: "http://example.com/".to_string(),
+                unicode_host_url: "http://example.com/".to_string(),
+                main_mirror: Some(Box::new(HostInfo::sample())),
+                ..FullHostInfo::sample()
+            }))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/diagnostics$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "problems": {
+                    "MAIN_MIRROR_IS_NOT_HTTPS": {
+                        "severity": "RECOMMENDATION",
+                        "state": "ABSENT"
+                    }
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let status = client
+            .check_https_migration("http:example.com:80")
+            .await
+            .unwrap();
+
+        assert!(!status.host_is_https);
+        assert!(status.main_mirror_is_https);
+        assert!(status.is_migrated());
+    }
+
+    #[tokio::test]
+    async fn test_get_url_indexing_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(query_param("url", "https://example.com/a"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "history": [
+                    {
+                        "url": "https://example.com/a",
+                        "indexing_status": {
+                            "status": "HTTP_2XX",
+                            "http_code": 200,
+                            "access_date": "2024-01-01T00:00:00Z"
+                        }
+                    },
+                    {
+                        "url": "https://example.com/a",
+                        "indexing_status": {
+                            "status": "HTTP_4XX",
+                            "http_code": 404,
+                            "access_date": "2024-01-02T00:00:00Z"
+                        }
+                    },
+                    { "url": "https://example.com/a" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "history": [] })))
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+
+        let status = client
+            .get_url_indexing_status(SAMPLE_HOST_ID, "https://example.com/a")
+            .await
+            .unwrap();
+        assert_eq!(status.http_code, Some(404));
+
+        let error = client
+            .get_url_indexing_status(SAMPLE_HOST_ID, "https://example.com/b")
+            .await
+            .unwrap_err();
+        assert!(matches!(
+            error,
+            YandexWebmasterError::IndexingStatusUnavailable(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_sitemaps() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path_regex("/user-added-sitemaps/deleted$"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "error_code": "SITEMAP_NOT_FOUND",
+                "error_message": "some string"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .and(path_regex("/user-added-sitemaps/forbidden$"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "error_code": "ACCESS_FORBIDDEN",
+                "error_message": "some string"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let results = client
+            .delete_sitemaps(SAMPLE_HOST_ID, &["ok", "deleted", "forbidden"])
+            .await;
+
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, vec!["ok", "deleted", "forbidden"]);
+        assert!(results[0].1.is_ok());
+        assert!(results[1].1.is_ok());
+        assert!(results[2].1.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_delete_hosts() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .and(path_regex("/hosts/http:deleted.com:80$"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "error_code": "HOST_NOT_FOUND",
+                "error_message": "some string"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("DELETE"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let host_ids = [SAMPLE_HOST_ID, "http:deleted.com:80", "not-a-host-id"];
+
+        let results = client
+            .delete_hosts(&host_ids, DeleteConfirmation::count(2))
+            .await;
+        assert_eq!(results.len(), 3);
+        assert!(results
+            .iter()
+            .all(|(_, r)| matches!(r, Err(YandexWebmasterError::InvalidRequest(_)))));
+
+        let results = client
+            .delete_hosts(&host_ids, DeleteConfirmation::count(3))
+            .await;
+        let ids: Vec<&str> = results.iter().map(|(id, _)| id.as_str()).collect();
+        assert_eq!(ids, host_ids);
+        assert!(results[0].1.is_ok());
+        assert!(results[1].1.is_ok());
+        assert!(matches!(
+            results[2].1,
+            Err(YandexWebmasterError::InvalidHostId(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_treat_already_added_as_success() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(format!("/user/{}/hosts", MOCK_USER_ID)))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_code": "HOST_ALREADY_ADDED",
+                "error_message": "some string",
+                "host_id": SAMPLE_HOST_ID,
+                "verified": true
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path_regex("/user-added-sitemaps$"))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_code": "SITEMAP_ALREADY_ADDED",
+                "error_message": "some string",
+                "sitemap_id": "existing"
+            })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo::sample()))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let lenient = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .treat_already_added_as_success(true),
+        );
+        let host = lenient
+            .add_host("https://example.com/", VerificationType::MetaTag)
+            .await
+            .unwrap();
+        assert_eq!(host.host_id, SAMPLE_HOST_ID);
+        let sitemap = lenient
+            .add_sitemap(SAMPLE_HOST_ID, "https://example.com/sitemap.xml")
+            .await
+            .unwrap();
+        assert_eq!(sitemap.sitemap_id, "existing");
+
+        let strict = YandexWebmasterClient::mock(server.uri());
+        let error = strict
+            .add_host("https://example.com/", VerificationType::MetaTag)
+            .await
+            .unwrap_err();
+        assert!(error.is_already_added());
+        let error = strict
+            .add_sitemap(SAMPLE_HOST_ID, "https://example.com/sitemap.xml")
+            .await
+            .unwrap_err();
+        assert!(error.is_already_added());
+    }
+
+    #[tokio::test]
+    async fn test_add_sitemaps() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path_regex("/user-added-sitemaps$"))
+            .and(body_json(json!({ "url": "https://example.com/old.xml" })))
+            .respond_with(ResponseTemplate::new(409).set_body_json(json!({
+                "error_code": "SITEMAP_ALREADY_ADDED",
+                "error_message": "some string",
+                "sitemap_id": "existing"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path_regex("/user-added-sitemaps$"))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "sitemap_id": "new" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let results = client
+            .add_sitemaps(
+                SAMPLE_HOST_ID,
+                &[
+                    "https://example.com/new.xml",
+                    "https://example.com/old.xml",
+                    "https://other.com/sitemap.xml",
+                ],
+            )
+            .await;
+
+        let ids: Vec<_> = results
+            .iter()
+            .map(|(url, r)| (url.as_str(), r.as_ref().ok().map(|r| r.sitemap_id.as_str())))
+            .collect();
+        assert_eq!(
+            ids,
+            [
+                ("https://example.com/new.xml", Some("new")),
+                ("https://example.com/old.xml", Some("existing")),
+                ("https://other.com/sitemap.xml", None)
+            ]
+        );
+        assert!(matches!(
+            results[2].1,
+            Err(YandexWebmasterError::SitemapHostMismatch { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_all_user_sitemaps() {
+        let server = MockServer::start().await;
+        let page = |offset: usize, len: usize, count: i32| {
+            let sitemaps: Vec<UserSitemapInfo> = (offset..offset + len)
+                .map(|i| UserSitemapInfo {
+                    sitemap_id: i.to_string(),
+                    ..UserSitemapInfo::sample()
+                })
+                .collect();
+            ResponseTemplate::new(200).set_body_json(UserSitemapsResponse { sitemaps, count })
+        };
+        Mock::given(method("GET"))
+            .and(query_param("offset", "0"))
+            .respond_with(page(0, 100, 150))
+            .mount(&server)
+            .await;
+        // A sitemap was deleted between the pages
+        Mock::given(method("GET"))
+            .and(query_param("offset", "100"))
+            .respond_with(page(100, 49, 149))
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let sitemaps = client.get_all_user_sitemaps(SAMPLE_HOST_ID).await.unwrap();
+
+        assert_eq!(sitemaps.len(), 149);
+        assert_eq!(sitemaps[148].sitemap_id, "148");
+    }
+
+    #[tokio::test]
+    async fn test_get_popular_queries_by_device() {
+        let server = MockServer::start().await;
+        for (device, count) in [("DESKTOP", 1), ("MOBILE", 2)] {
+            Mock::given(method("GET"))
+                .and(query_param("device_type_indicator", device))
+                .and(query_param("order_by", "TOTAL_CLICKS"))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "queries": [],
+                    "date_from": "2024-01-01",
+                    "date_to": "2024-01-31",
+                    "count": count
+                })))
+                .mount(&server)
+                .await;
+        }
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let request = PopularQueriesRequest::builder()
+            .order_by(ApiQueryOrderField::TotalClicks)
+            .build();
+        let result = client
+            .get_popular_queries_by_device(
+                SAMPLE_HOST_ID,
+                &request,
+                &[
+                    ApiDeviceTypeIndicator::Desktop,
+                    ApiDeviceTypeIndicator::Mobile,
+                ],
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(result.len(), 2);
+        assert_eq!(result[&ApiDeviceTypeIndicator::Desktop].count, 1);
+        assert_eq!(result[&ApiDeviceTypeIndicator::Mobile].count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_unstructured_error_classified() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(429).set_body_string("<html>Too Many Requests</html>"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let error = client.get_host(SAMPLE_HOST_ID).await.unwrap_err();
+
+        assert!(error.is_rate_limited());
+        match error {
+            YandexWebmasterError::UnstructuredApiError { status, body, .. } => {
+                assert_eq!(status, 429);
+                assert_eq!(body, "<html>Too Many Requests</html>");
+            }
+            other => panic!("unexpected error: {other:?}"),
         }
+    }
+
+    #[tokio::test]
+    async fn test_lazy_user_id() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/user"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "user_id": 42 })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("/user/42/hosts/{}", SAMPLE_HOST_ID)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo::sample()))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+            .base_url(server.uri())
+            .build_lazy()
+            .unwrap();
+        assert_eq!(client.user_id(), 0);
+
+        client.get_host(SAMPLE_HOST_ID).await.unwrap();
+        client.clone().get_host(SAMPLE_HOST_ID).await.unwrap();
+
+        assert_eq!(client.user_id(), 42);
+        assert_eq!(client.get_user_id().await.unwrap(), 42);
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_requests() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(FullHostInfo::sample())
+                    .set_delay(std::time::Duration::from_millis(200)),
+            )
+            .mount(&server)
+            .await;
+
+        let client = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .max_concurrent_requests(1),
+        );
+
+        let started = std::time::Instant::now();
+        let (first, second) = tokio::join!(
+            client.get_host(SAMPLE_HOST_ID),
+            client.get_host(SAMPLE_HOST_ID)
+        );
+        first.unwrap();
+        second.unwrap();
+
+        assert!(started.elapsed() >= std::time::Duration::from_millis(400));
+    }
+
+    #[tokio::test]
+    async fn test_max_concurrent_requests_zero_is_unlimited() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo::sample()))
+            .mount(&server)
+            .await;
+
+        let client = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .max_concurrent_requests(0),
+        );
+
+        tokio::time::timeout(
+            std::time::Duration::from_secs(5),
+            client.get_host(SAMPLE_HOST_ID),
+        )
+        .await
+        .expect("request waited for a slot")
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_token_refresher() {
+        let server = MockServer::start().await;
+        Mock::given(header("Authorization", "OAuth fresh-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo::sample()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(403).set_body_json(json!({
+                "error_code": "INVALID_OAUTH_TOKEN",
+                "error_message": "token expired"
+            })))
+            .mount(&server)
+            .await;
+
+        let refreshes = Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = refreshes.clone();
+        let refreshing = client(
+            YandexWebmasterClient::builder("expired-token".to_string())
+                .base_url(server.uri())
+                .token_refresher(move || {
+                    let token = match counter.fetch_add(1, Ordering::SeqCst) {
+                        0 => "fresh-token",
+                        _ => "rejected-token",
+                    };
+                    async move { Ok(token.to_string()) }
+                }),
+        );
+
+        refreshing.get_host(SAMPLE_HOST_ID).await.unwrap();
+        refreshing.get_host(SAMPLE_HOST_ID).await.unwrap();
+        assert_eq!(refreshes.load(Ordering::SeqCst), 1);
+
+        let looping = client(
+            YandexWebmasterClient::builder("expired-token".to_string())
+                .base_url(server.uri())
+                .token_refresher(|| async { Ok("rejected-token".to_string()) }),
+        );
+        let error = looping.get_host(SAMPLE_HOST_ID).await.unwrap_err();
+        assert_eq!(
+            error.error_code(),
+            Some(&YandexErrorCode::InvalidOauthToken)
+        );
+        assert_eq!(server.received_requests().await.unwrap().len(), 5);
+    }
+
+    #[tokio::test]
+    async fn test_get_pending_recrawl_tasks() {
+        let server = MockServer::start().await;
+        let in_progress = RecrawlTask {
+            state: RecrawlTaskState::InProgress,
+            ..RecrawlTask::sample()
+        };
+        let first_page: Vec<_> = (0..100)
+            .map(|i| {
+                if i % 2 == 0 {
+                    in_progress.clone()
+                } else {
+                    RecrawlTask::sample()
+                }
+            })
+            .collect();
+        Mock::given(method("GET"))
+            .and(query_param("offset", "0"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(RecrawlTasksResponse { tasks: first_page }),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(query_param("offset", "100"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(RecrawlTasksResponse {
+                    tasks: vec![in_progress.clone(), RecrawlTask::sample()],
+                }),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let pending = client
+            .get_pending_recrawl_tasks(SAMPLE_HOST_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(pending.len(), 51);
+        assert!(pending
+            .iter()
+            .all(|task| task.state == RecrawlTaskState::InProgress));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_exhausted() {
+        let server = MockServer::start().await;
+        Mock::given(method("DELETE"))
+            .respond_with(
+                ResponseTemplate::new(429)
+                    .insert_header("Retry-After", "120")
+                    .set_body_json(json!({
+                        "error_code": "QUOTA_EXCEEDED",
+                        "error_message": "quota exceeded"
+                    })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        assert_eq!(client.check_rate_limit(), RateLimitStatus::Available);
+
+        let error = client
+            .delete_sitemap(SAMPLE_HOST_ID, "1")
+            .await
+            .unwrap_err();
+        assert!(error.is_rate_limited());
+
+        let results = client.delete_sitemaps(SAMPLE_HOST_ID, &["2", "3"]).await;
+        assert!(results.iter().all(|(_, result)| matches!(
+            result,
+            Err(YandexWebmasterError::RateLimitExhausted { .. })
+        )));
+
+        match client.clone().check_rate_limit() {
+            RateLimitStatus::Exhausted { retry_after } => {
+                assert!(retry_after > Duration::from_secs(100));
+            }
+            status => panic!("unexpected status: {status:?}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_hosts_detailed() {
+        let server = MockServer::start().await;
+        let missing = HostInfo {
+            host_id: "https:missing.com:443".to_string(),
+            ..HostInfo::sample()
+        };
+        Mock::given(path(format!("/user/{}/hosts", MOCK_USER_ID)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostsResponse {
+                hosts: vec![HostInfo::sample(), missing],
+            }))
+            .mount(&server)
+            .await;
+        Mock::given(path(format!(
+            "/user/{}/hosts/{}",
+            MOCK_USER_ID, SAMPLE_HOST_ID
+        )))
+        .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo::sample()))
+        .mount(&server)
+        .await;
+        Mock::given(path(format!(
+            "/user/{}/hosts/https:missing.com:443",
+            MOCK_USER_ID
+        )))
+        .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+            "error_code": "HOST_NOT_FOUND",
+            "error_message": "host not found"
+        })))
+        .mount(&server)
+        .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let result = client.get_hosts_detailed().await.unwrap();
+
+        assert!(!result.is_complete());
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].0, "https:missing.com:443");
+        assert!(result.errors[0].1.is_not_found());
+        assert_eq!(result.into_result().unwrap(), vec![FullHostInfo::sample()]);
+
+        let all_failed: PartialResult<()> = vec![(
+            "https:missing.com:443".to_string(),
+            Err(YandexWebmasterError::NoVerifiedHosts),
+        )]
+        .into_iter()
+        .collect();
+        assert!(all_failed.into_result().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_hosts_by_verification() {
+        let server = MockServer::start().await;
+        let pending = HostInfo {
+            host_id: "https:pending.com:443".to_string(),
+            verified: false,
+            ..HostInfo::sample()
+        };
+        Mock::given(path(format!("/user/{}/hosts", MOCK_USER_ID)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostsResponse {
+                hosts: vec![HostInfo::sample(), pending.clone()],
+            }))
+            .mount(&server)
+            .await;
+        Mock::given(path_regex("/verification$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "verification_state": "IN_PROGRESS",
+                "verification_type": "DNS",
+                "verification_uin": "d4f3a9b1c2e5f678",
+                "applicable_verifiers": ["DNS"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let result = client.get_hosts_by_verification().await.unwrap();
+
+        assert!(result.is_complete());
+        assert_eq!(
+            result.ok,
+            vec![
+                (VerificationState::Verified, HostInfo::sample()),
+                (VerificationState::InProgress, pending)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_hosts_by_verification_partial() {
+        let server = MockServer::start().await;
+        let pending = |host_id: &str| HostInfo {
+            host_id: host_id.to_string(),
+            verified: false,
+            ..HostInfo::sample()
+        };
+        Mock::given(path(format!("/user/{}/hosts", MOCK_USER_ID)))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostsResponse {
+                hosts: vec![
+                    pending("https:ok.com:443"),
+                    pending("https:failing.com:443"),
+                ],
+            }))
+            .mount(&server)
+            .await;
+        Mock::given(path_regex("/hosts/https:failing.com:443/verification$"))
+            .respond_with(ResponseTemplate::new(500).set_body_json(json!({
+                "error_code": "INTERNAL_ERROR",
+                "error_message": "some string"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(path_regex("/verification$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "verification_state": "NONE",
+                "verification_type": "DNS",
+                "verification_uin": "d4f3a9b1c2e5f678",
+                "applicable_verifiers": ["DNS"]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let result = client.get_hosts_by_verification().await.unwrap();
+
+        assert_eq!(result.ok.len(), 1);
+        assert_eq!(result.ok[0].1.host_id, "https:ok.com:443");
+        assert_eq!(result.errors.len(), 1);
+        assert_eq!(result.errors[0].0, "https:failing.com:443");
+    }
+
+    #[tokio::test]
+    async fn test_get_search_events_samples_stream() {
+        let server = MockServer::start().await;
+        let sample = |i: usize| {
+            json!({
+                "url": format!("https://example.com/{i}"),
+                "title": "Page",
+                "event_date": "2024-01-01T00:00:00.000+0300",
+                "last_access": "2024-01-01T00:00:00.000+0300",
+                "event": "REMOVED_FROM_SEARCH",
+                "excluded_url_status": "NO_INDEX"
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path_regex("/search-urls/events/samples$"))
+            .and(query_param("event", "REMOVED_FROM_SEARCH"))
+            .and(query_param("offset", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 101,
+                "samples": (0..100).map(sample).collect::<Vec<_>>()
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/search-urls/events/samples$"))
+            .and(query_param("offset", "100"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "count": 101,
+                "samples": [sample(100)]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let samples: Vec<SearchEventsSample> = client
+            .get_search_events_samples_stream(
+                SAMPLE_HOST_ID,
+                Some(ApiSearchEventEnum::RemovedFromSearch),
+            )
+            .try_collect()
+            .await
+            .unwrap();
+
+        assert_eq!(samples.len(), 101);
+        assert_eq!(samples[100].url, "https://example.com/100");
+    }
+
+    #[tokio::test]
+    async fn test_get_pages_appeared_since() {
+        let server = MockServer::start().await;
+        let newest = "2024-03-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let sample = |i: usize| {
+            json!({
+                "url": format!("https://example.com/{i}"),
+                "title": "Page",
+                "event_date": newest - chrono::Duration::hours(i as i64),
+                "last_access": newest - chrono::Duration::hours(i as i64),
+                "event": if i == 5 { "REMOVED_FROM_SEARCH" } else { "APPEARED_IN_SEARCH" }
+            })
+        };
+        // The first page starts with an old sample followed by new ones
+        let first_page: Vec<_> = [200, 1, 150, 5, 2].into_iter().chain(300..395).collect();
+        let pages = [first_page, (3..5).chain(400..498).collect(), vec![6]];
+        for (page, samples) in pages.iter().enumerate() {
+            Mock::given(method("GET"))
+                .and(path_regex("/search-urls/events/samples$"))
+                .and(query_param("event", "APPEARED_IN_SEARCH"))
+                .and(query_param("offset", (page * 100).to_string()))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "count": 201,
+                    "samples": samples.iter().copied().map(sample).collect::<Vec<_>>()
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let samples = client
+            .get_pages_appeared_since(SAMPLE_HOST_ID, newest - chrono::Duration::hours(100))
+            .await
+            .unwrap();
+
+        let urls: Vec<_> = samples.iter().map(|s| s.url.as_str()).collect();
+        assert_eq!(
+            urls,
+            [
+                "https://example.com/1",
+                "https://example.com/2",
+                "https://example.com/3",
+                "https://example.com/4",
+                "https://example.com/6"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_invalid_host_id() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostSummaryResponse::sample()))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        for host_id in ["", "example.com", "https://example.com/"] {
+            let error = client.get_host_summary(host_id).await.unwrap_err();
+            assert!(
+                matches!(error, YandexWebmasterError::InvalidHostId(ref id) if id == host_id),
+                "unexpected error for {host_id:?}: {error:?}"
+            );
+        }
+
+        client
+            .get_host_summary("https:xn--d1acpjx3f.xn--p1ai:8443")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_popular_queries_matching() {
+        let server = MockServer::start().await;
+        let query = |i: usize| {
+            json!({
+                "query_id": i.to_string(),
+                "query_text": if i.is_multiple_of(250) { format!("Buy Shoes {i}") } else { format!("query {i}") },
+                "indicators": { "TOTAL_SHOWS": 10.0 }
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path_regex("/search-queries/popular$"))
+            .and(query_param("offset", "0"))
+            .and(query_param("limit", "500"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "queries": (0..500).map(query).collect::<Vec<_>>(),
+                "date_from": "2024-01-01",
+                "date_to": "2024-01-31",
+                "count": 501
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/search-queries/popular$"))
+            .and(query_param("offset", "500"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "queries": [query(500)],
+                "date_from": "2024-01-01",
+                "date_to": "2024-01-31",
+                "count": 501
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let request = PopularQueriesRequest::builder()
+            .order_by(ApiQueryOrderField::TotalShows)
+            .build();
+        let queries = client
+            .get_popular_queries_matching(SAMPLE_HOST_ID, &request, "shoes")
+            .await
+            .unwrap();
+
+        let texts: Vec<_> = queries.iter().map(|q| q.query_text.as_str()).collect();
+        assert_eq!(texts, ["Buy Shoes 0", "Buy Shoes 250", "Buy Shoes 500"]);
+    }
+
+    #[tokio::test]
+    async fn test_response_cache() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path_regex("/summary$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostSummaryResponse::sample()))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/diagnostics$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "problems": {} })))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/user/{}/hosts/{}",
+                MOCK_USER_ID, SAMPLE_HOST_ID
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo::sample()))
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({ "task_id": "1" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .response_cache(crate::InMemoryResponseCache::new(10))
+                .endpoint_cache_ttl("/diagnostics", Duration::ZERO),
+        );
+
+        for _ in 0..2 {
+            let summary = client.get_host_summary(SAMPLE_HOST_ID).await.unwrap();
+            assert_eq!(summary, HostSummaryResponse::sample());
+            client.get_diagnostics(SAMPLE_HOST_ID).await.unwrap();
+            client.get_host(SAMPLE_HOST_ID).await.unwrap();
+        }
+
+        // A mutation of the site invalidates its cached responses
+        client
+            .recrawl_urls(SAMPLE_HOST_ID, "https://example.com/")
+            .await
+            .unwrap();
+        client.get_host_summary(SAMPLE_HOST_ID).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_get_query_analytics_chunked() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path_regex("/search-queries/all/history$"))
+            .and(query_param("date_from", "2024-01-01T00:00:00Z"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "indicators": {
+                    "TOTAL_SHOWS": [
+                        { "date": "2024-01-01T00:00:00.000+0000", "value": 10.0 },
+                        { "date": "2024-01-31T00:00:00.000+0000", "value": 11.0 }
+                    ]
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/search-queries/all/history$"))
+            .and(query_param("date_from", "2024-01-31T00:00:00Z"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error_code": "SOME_DATES_ARE_UNAVAILABLE",
+                "error_message": "some string"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/search-queries/all/history$"))
+            .and(query_param("date_from", "2024-03-01T00:00:00Z"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "indicators": {
+                    "TOTAL_SHOWS": [
+                        { "date": "2024-03-01T00:00:00.000+0000", "value": 12.0 }
+                    ]
+                }
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let request = QueryAnalyticsRequest::builder()
+            .query_indicator(vec![ApiQueryIndicator::TotalShows])
+            .date_from("2024-01-01T00:00:00Z".parse::<DateTime<Utc>>().unwrap())
+            .date_to("2024-03-10T00:00:00Z".parse::<DateTime<Utc>>().unwrap())
+            .build()
+            .unwrap();
+        let response = client
+            .get_query_analytics_chunked(SAMPLE_HOST_ID, &request, chrono::Duration::days(30))
+            .await
+            .unwrap();
+
+        let values: Vec<f64> = response.indicators[&ApiQueryIndicator::TotalShows]
+            .iter()
+            .map(|point| point.value)
+            .collect();
+        assert_eq!(values, [10.0, 11.0, 12.0]);
+
+        let request = QueryAnalyticsRequest::builder()
+            .query_indicator(vec![ApiQueryIndicator::TotalShows])
+            .build()
+            .unwrap();
+        let error = client
+            .get_query_analytics_chunked(SAMPLE_HOST_ID, &request, chrono::Duration::days(30))
+            .await
+            .unwrap_err();
+        assert!(matches!(error, YandexWebmasterError::InvalidRequest(_)));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_recrawl_batch() {
+        let server = MockServer::start().await;
+        let task = |task_id: &str, state: &str| {
+            json!({
+                "task_id": task_id,
+                "url": "https://example.com/",
+                "added_time": "2024-01-01T00:00:00.000+0300",
+                "state": state
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path_regex("/recrawl/queue$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "tasks": [task("1", "IN_PROGRESS"), task("2", "DONE")]
+            })))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/recrawl/queue$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "tasks": [task("1", "FAILED"), task("2", "DONE")]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/recrawl/queue/3$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(task("3", "DONE")))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let tasks = client
+            .wait_for_recrawl_batch(
+                SAMPLE_HOST_ID,
+                &["3", "2", "1"],
+                Duration::from_millis(10),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+
+        let states: Vec<_> = tasks
+            .iter()
+            .map(|t| (t.task_id.as_str(), t.state))
+            .collect();
+        assert_eq!(
+            states,
+            [
+                ("3", RecrawlTaskState::Done),
+                ("2", RecrawlTaskState::Done),
+                ("1", RecrawlTaskState::Failed)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_problem_resolved() {
+        let server = MockServer::start().await;
+        let diagnostics = |state: &str| {
+            json!({
+                "problems": {
+                    "NO_SITEMAPS": {
+                        "severity": "POSSIBLE_PROBLEM",
+                        "state": state,
+                        "last_state_update": "2024-01-01T00:00:00.000+0300"
+                    }
+                }
+            })
+        };
+        Mock::given(method("GET"))
+            .and(path_regex("/diagnostics$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(diagnostics("PRESENT")))
+            .up_to_n_times(2)
+            .expect(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/diagnostics$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(diagnostics("ABSENT")))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let resolved = client
+            .wait_for_problem_resolved(
+                SAMPLE_HOST_ID,
+                ApiSiteProblemTypeEnum::NoSitemaps,
+                Duration::from_millis(10),
+                Duration::from_secs(5),
+            )
+            .await
+            .unwrap();
+        assert!(resolved);
+
+        let resolved = client
+            .wait_for_problem_resolved(
+                SAMPLE_HOST_ID,
+                ApiSiteProblemTypeEnum::ConnectFailed,
+                Duration::from_millis(10),
+                Duration::ZERO,
+            )
+            .await
+            .unwrap();
+        assert!(resolved);
+    }
+
+    #[tokio::test]
+    async fn test_get_query_histories_table() {
+        let server = MockServer::start().await;
+        for (query_id, query_text) in [("q1", "first"), ("q2", "second")] {
+            Mock::given(method("GET"))
+                .and(path_regex(format!("/search-queries/{query_id}/history$")))
+                .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                    "query_id": query_id,
+                    "query_text": query_text,
+                    "indicators": {
+                        "TOTAL_SHOWS": [
+                            { "date": "2024-01-02T00:00:00.000+0300", "value": 20.0 },
+                            { "date": "2024-01-01T00:00:00.000+0300", "value": 10.0 }
+                        ]
+                    }
+                })))
+                .expect(1)
+                .mount(&server)
+                .await;
+        }
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let request = QueryHistoryRequest::builder()
+            .query_indicator([ApiQueryIndicator::TotalShows])
+            .build()
+            .unwrap();
+        let rows = client
+            .get_query_histories_table(SAMPLE_HOST_ID, &["q2", "q1"], &request)
+            .await
+            .unwrap();
+
+        let table: Vec<_> = rows
+            .iter()
+            .map(|r| (r.query_id.as_str(), r.query_text.as_str(), r.value))
+            .collect();
+        assert_eq!(
+            table,
+            [
+                ("q2", "second", 10.0),
+                ("q2", "second", 20.0),
+                ("q1", "first", 10.0),
+                ("q1", "first", 20.0)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_stale_sitemaps() {
+        let server = MockServer::start().await;
+        let sitemap = |sitemap_id: &str, last_access_date: Option<DateTime<Utc>>| SitemapInfo {
+            sitemap_id: sitemap_id.to_string(),
+            last_access_date,
+            ..SitemapInfo::sample()
+        };
+        Mock::given(method("GET"))
+            .and(path_regex("/sitemaps$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "sitemaps": [
+                    sitemap("recent", Some(Utc::now() - chrono::Duration::days(1))),
+                    sitemap("stale", Some(Utc::now() - chrono::Duration::days(30))),
+                    sitemap("never", None)
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let sitemaps = client
+            .get_stale_sitemaps(SAMPLE_HOST_ID, chrono::Duration::days(7))
+            .await
+            .unwrap();
+
+        let ids: Vec<&str> = sitemaps.iter().map(|s| s.sitemap_id.as_str()).collect();
+        assert_eq!(ids, ["stale", "never"]);
+    }
+
+    #[tokio::test]
+    async fn test_connection_retries() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(HostSummaryResponse::sample())
+                    .set_delay(Duration::from_secs(1)),
+            )
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostSummaryResponse::sample()))
+            .mount(&server)
+            .await;
+
+        let builder = |retries| {
+            let http = reqwest::Client::builder()
+                .timeout(Duration::from_millis(200))
+                .build()
+                .unwrap();
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .client(ClientBuilder::new(http))
+                .connection_retries(retries)
+        };
+
+        let summary = client(builder(1))
+            .get_host_summary(SAMPLE_HOST_ID)
+            .await
+            .unwrap();
+        assert_eq!(summary, HostSummaryResponse::sample());
+
+        server.reset().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(HostSummaryResponse::sample())
+                    .set_delay(Duration::from_secs(1)),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        let error = client(builder(0))
+            .get_host_summary(SAMPLE_HOST_ID)
+            .await
+            .unwrap_err();
+        assert!(error.is_retryable());
+    }
+
+    #[tokio::test]
+    async fn test_index_now() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/indexnow"))
+            .and(body_json(json!({
+                "host": "example.com",
+                "key": "0123456789abcdef",
+                "keyLocation": "https://example.com/keys/indexnow.txt",
+                "urlList": ["https://example.com/a", "https://example.com/b"]
+            })))
+            .respond_with(ResponseTemplate::new(202))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let builder = YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+            .base_url(server.uri())
+            .index_now_key("0123456789abcdef".to_string())
+            .index_now_key_location("https://example.com/keys/indexnow.txt".to_string())
+            .index_now_url(format!("{}/indexnow", server.uri()));
+        let client = client(builder);
+
+        client
+            .index_now(
+                SAMPLE_HOST_ID,
+                &["https://example.com/a", "https://example.com/b"],
+            )
+            .await
+            .unwrap();
+        let requests = server.received_requests().await.unwrap();
+        assert!(requests[0]
+            .headers
+            .get(reqwest::header::AUTHORIZATION)
+            .is_none());
+
+        let result = client
+            .index_now(SAMPLE_HOST_ID, &["https://other.com/a"])
+            .await;
+        assert!(matches!(
+            result,
+            Err(YandexWebmasterError::InvalidRequest(_))
+        ));
+
+        let result = YandexWebmasterClient::mock(server.uri())
+            .index_now(SAMPLE_HOST_ID, &["https://example.com/a"])
+            .await;
+        assert!(matches!(
+            result,
+            Err(YandexWebmasterError::InvalidRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_max_body_size() {
+        let server = MockServer::start().await;
+        let feed = |i: usize| format!("https://example.com/feed{i}.xml");
+        Mock::given(method("POST"))
+            .and(path_regex("/feeds/batch/remove$"))
+            .and(body_json(json!({ "urls": [feed(1), feed(2)] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path_regex("/feeds/batch/remove$"))
+            .and(body_json(json!({ "urls": [feed(3)] })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let builder = YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+            .base_url(server.uri())
+            .max_body_size(80);
+        let client = client(builder);
+
+        client
+            .delete_feeds(SAMPLE_HOST_ID, &[&feed(1), &feed(2), &feed(3)])
+            .await
+            .unwrap();
+
+        let long_url = format!("https://example.com/{}", "a".repeat(100));
+        let result = client.delete_feed(SAMPLE_HOST_ID, &long_url).await;
+        assert!(matches!(
+            result,
+            Err(YandexWebmasterError::PayloadTooLarge { limit: 80, .. })
+        ));
+        let result = client.recrawl_urls(SAMPLE_HOST_ID, &long_url).await;
+        assert!(matches!(
+            result,
+            Err(YandexWebmasterError::PayloadTooLarge {
+                size: 130,
+                limit: 80
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_delete_feed_strict_deserialization() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path_regex("/feeds/batch/remove$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "feeds": [{ "url": "https://example.com/feed.xml", "status": "OK" }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let builder = YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+            .base_url(server.uri())
+            .strict_deserialization(true);
+        let client = client(builder);
+
+        client
+            .delete_feed(SAMPLE_HOST_ID, "https://example.com/feed.xml")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_slow_request_threshold() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(FullHostInfo::sample())
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .mount(&server)
+            .await;
+
+        let recording = RecordingSubscriber::default();
+        let _guard = tracing::subscriber::set_default(recording.clone());
+        let slow_requests = || {
+            recording
+                .warnings()
+                .iter()
+                .filter(|message| *message == "Slow Yandex Webmaster API request")
+                .count()
+        };
+
+        let patient = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .slow_request_threshold(Duration::from_secs(5)),
+        );
+        patient.get_host(SAMPLE_HOST_ID).await.unwrap();
+        assert_eq!(slow_requests(), 0);
+
+        let impatient = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .slow_request_threshold(Duration::from_millis(50)),
+        );
+        impatient.get_host(SAMPLE_HOST_ID).await.unwrap();
+        assert_eq!(slow_requests(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_parent_span() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path_regex("/sitemaps$"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "sitemaps": [SitemapInfo::sample()] })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/user/{}/hosts/{}",
+                MOCK_USER_ID, SAMPLE_HOST_ID
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo::sample()))
+            .mount(&server)
+            .await;
+
+        let recording = RecordingSubscriber::default();
+        let _guard = tracing::subscriber::set_default(recording.clone());
+        let client = client(
+            YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+                .base_url(server.uri())
+                .parent_span(tracing::info_span!("yandex")),
+        );
+
+        client.get_all_sitemaps(SAMPLE_HOST_ID).await.unwrap();
+        client
+            .get_host(SAMPLE_HOST_ID)
+            .instrument(tracing::info_span!(target: "app", "handler"))
+            .await
+            .unwrap();
+
+        assert_eq!(recording.parents("get_all_sitemaps"), [Some("yandex")]);
+        assert_eq!(
+            recording.parents("get_sitemaps"),
+            [Some("get_all_sitemaps")]
+        );
+        assert_eq!(recording.parents("get_host"), [Some("yandex")]);
+    }
+
+    #[test]
+    fn test_is_same_host() {
+        let same = YandexWebmasterClient::is_same_host;
+        assert!(same(
+            "https://example.com/",
+            "https://EXAMPLE.com/sitemap.xml"
+        ));
+        assert!(same("https://example.com:443/", "https://example.com/a"));
+        assert!(same("http://example.com/", "http://example.com:80/a"));
+        assert!(same(
+            "https://example.com:8443/",
+            "https://example.com:8443/a"
+        ));
+        assert!(!same("https://example.com/", "http://example.com/a"));
+        assert!(!same("https://example.com/", "https://example.com:8443/a"));
+        assert!(!same("https://example.com/", "https://www.example.com/a"));
+        assert!(!same("https://example.com/", "/sitemap.xml"));
+    }
+
+    #[test]
+    fn test_path_segment() {
+        assert!(matches!(
+            path_segment("https:example.com:443"),
+            Cow::Borrowed("https:example.com:443")
+        ));
+        assert_eq!(path_segment("a/b?c#d%e f"), "a%2Fb%3Fc%23d%25e%20f");
+        assert_eq!(path_segment("карта"), "%D0%BA%D0%B0%D1%80%D1%82%D0%B0");
+    }
+
+    #[tokio::test]
+    async fn test_path_segment_reserved_characters() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/user/{}/hosts/{}/user-added-sitemaps/c7%2Ffe%3F80%23c0",
+                MOCK_USER_ID, SAMPLE_HOST_ID
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(UserSitemapInfo::sample()))
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let sitemap = client
+            .get_user_sitemap(SAMPLE_HOST_ID, "c7/fe?80#c0")
+            .await
+            .unwrap();
+        assert_eq!(sitemap, UserSitemapInfo::sample());
+    }
+
+    #[tokio::test]
+    async fn test_get_applicable_verifiers() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/user/{}/hosts/{}/verification",
+                MOCK_USER_ID, SAMPLE_HOST_ID
+            )))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "verification_state": "NONE",
+                "verification_type": "META_TAG",
+                "verification_uin": "d4f3a9b1c2e5f678",
+                "applicable_verifiers": ["DNS", "HTML_FILE"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let verifiers = client
+            .get_applicable_verifiers(SAMPLE_HOST_ID)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            verifiers,
+            [
+                ExplicitVerificationType::Dns,
+                ExplicitVerificationType::HtmlFile
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_add_host_auto_verify() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(format!("/user/{}/hosts", MOCK_USER_ID)))
+            .respond_with(
+                ResponseTemplate::new(201).set_body_json(json!({ "host_id": SAMPLE_HOST_ID })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/verification$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "verification_state": "NONE",
+                "verification_type": "META_TAG",
+                "verification_uin": "d4f3a9b1c2e5f678",
+                "applicable_verifiers": ["DNS", "HTML_FILE"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let instructions = client
+            .add_host_auto_verify("https://example.com/")
+            .await
+            .unwrap();
+
+        assert_eq!(instructions.host_id, SAMPLE_HOST_ID);
+        assert_eq!(
+            instructions.verification_type,
+            ExplicitVerificationType::HtmlFile
+        );
+        assert_eq!(instructions.verification_uin.as_str(), "d4f3a9b1c2e5f678");
+        assert_eq!(
+            instructions.detail,
+            VerificationDetail::HtmlFile {
+                file_name: "yandex_d4f3a9b1c2e5f678.html".to_string(),
+                content: instructions.verification_uin.as_html_file_content(),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_content_type_unsupported() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("Content-Type", JSON_CONTENT_TYPE))
+            .respond_with(ResponseTemplate::new(406).set_body_json(json!({
+                "error_code": "CONTENT_TYPE_UNSUPPORTED",
+                "error_message": "some string",
+                "acceptable_types": ["application/json"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let error = client
+            .recrawl_urls(SAMPLE_HOST_ID, "https://example.com/")
+            .await
+            .unwrap_err();
+
+        match &error {
+            YandexWebmasterError::ContentTypeUnsupported {
+                status,
+                acceptable_types,
+                ..
+            } => {
+                assert_eq!(*status, 406);
+                assert_eq!(acceptable_types, &["application/json"]);
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert_eq!(
+            error.error_code(),
+            Some(&YandexErrorCode::ContentTypeUnsupported)
+        );
+
+        Mock::given(method("POST"))
+            .and(header("Content-Type", "application/json"))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({ "task_id": "1" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        client
+            .with_content_type("application/json".to_string())
+            .recrawl_urls(SAMPLE_HOST_ID, "https://example.com/")
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_method_not_allowed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(405)
+                    .insert_header("Allow", "GET, DELETE")
+                    .set_body_string("Method Not Allowed"),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let error = client
+            .recrawl_urls(SAMPLE_HOST_ID, "https://example.com/")
+            .await
+            .unwrap_err();
+
+        match &error {
+            YandexWebmasterError::MethodNotAllowed { allow, body, .. } => {
+                assert_eq!(allow, &["GET", "DELETE"]);
+                assert_eq!(body, "Method Not Allowed");
+            }
+            other => panic!("unexpected error: {other:?}"),
+        }
+        assert_eq!(error.error_code(), Some(&YandexErrorCode::MethodNotAllowed));
+        assert_eq!(error.kind(), crate::ErrorKind::Validation);
+    }
+
+    #[tokio::test]
+    async fn test_get_sitemaps_detailed() {
+        let server = MockServer::start().await;
+        Mock::given(path_regex("/sitemaps/c7-fe:80-c0$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(SitemapInfo::sample()))
+            .mount(&server)
+            .await;
+        Mock::given(path_regex("/sitemaps/missing$"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "error_code": "SITEMAP_NOT_FOUND",
+                "error_message": "sitemap not found"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let results = client
+            .get_sitemaps_detailed(SAMPLE_HOST_ID, &["missing", "c7-fe:80-c0"])
+            .await;
+
+        assert_eq!(results[0].0, "missing");
+        assert!(results[0].1.as_ref().is_err_and(|e| e.is_not_found()));
+        assert_eq!(results[1].0, "c7-fe:80-c0");
+        assert_eq!(results[1].1.as_ref().unwrap(), &SitemapInfo::sample());
+    }
+
+    #[tokio::test]
+    async fn test_get_full_sqi_history() {
+        let server = MockServer::start().await;
+        let points = |dates: &[&str]| {
+            json!({
+                "points": dates
+                    .iter()
+                    .map(|date| json!({ "date": date, "value": 10.0 }))
+                    .collect::<Vec<_>>()
+            })
+        };
+        Mock::given(path_regex("/sqi-history$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(points(&[
+                "2024-01-01T00:00:00.000+0300",
+                "2024-03-31T00:00:00.000+0300",
+            ])))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(path_regex("/sqi-history$"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(json!({
+                "error_code": "SOME_DATES_ARE_UNAVAILABLE",
+                "error_message": "some dates are unavailable"
+            })))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        Mock::given(path_regex("/sqi-history$"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(points(&["2024-07-01T00:00:00.000+0300"])),
+            )
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let date_from = "2024-01-01T00:00:00Z".parse().unwrap();
+        let history = client
+            .get_full_sqi_history(
+                SAMPLE_HOST_ID,
+                date_from,
+                date_from + chrono::Duration::days(200),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(history.len(), 3);
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
 
-        let data: T = response.json().await?;
-        Ok(data)
+        let error = client
+            .get_full_sqi_history(
+                SAMPLE_HOST_ID,
+                date_from + chrono::Duration::days(1),
+                date_from,
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error, YandexWebmasterError::InvalidRequest(_)));
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
     }
 }
@@ -1,8 +1,90 @@
-use chrono::{DateTime, NaiveDate, Utc};
+use chrono::{DateTime, Duration, NaiveDate, Utc};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+    ops::RangeInclusive,
+};
 use typed_builder::TypedBuilder;
 
+use crate::error::{Result, YandexWebmasterError};
+
+/// Returns the range from `days` days ago to now
+fn last_days_range(days: i64) -> (DateTime<Utc>, DateTime<Utc>) {
+    let now = Utc::now();
+    (now - Duration::days(days), now)
+}
+
+/// Returns the range of dates from `days` days ago to today (UTC)
+fn last_days_date_range(days: i64) -> (NaiveDate, NaiveDate) {
+    let today = Utc::now().date_naive();
+    (today - Duration::days(days), today)
+}
+
+/// Merges the per-key histories of several date ranges, sorting points by date
+///
+/// Points with the same date are duplicates from overlapping ranges; the one from the
+/// latest history in `histories` is kept.
+fn merge_histories<K: Eq + std::hash::Hash, P>(
+    histories: impl IntoIterator<Item = HashMap<K, Vec<P>>>,
+    date: impl Fn(&P) -> DateTime<Utc>,
+) -> HashMap<K, Vec<P>> {
+    let mut merged: HashMap<K, Vec<P>> = HashMap::new();
+    for history in histories {
+        for (key, points) in history {
+            merged.entry(key).or_default().extend(points);
+        }
+    }
+
+    for points in merged.values_mut() {
+        // Stable sort keeps later histories after earlier ones for the same date
+        points.sort_by_key(&date);
+        let mut deduped: Vec<P> = Vec::with_capacity(points.len());
+        for point in points.drain(..) {
+            match deduped.last_mut() {
+                Some(last) if date(last) == date(&point) => *last = point,
+                _ => deduped.push(point),
+            }
+        }
+        *points = deduped;
+    }
+
+    merged
+}
+
+/// Implements `to_query_string` for requests sent as query parameters
+macro_rules! impl_to_query_string {
+    ($($request:ty),* $(,)?) => {
+        $(
+            impl $request {
+                /// Encodes the request as the query string sent by the client
+                ///
+                /// Useful for logging the exact parameters of a request.
+                pub fn to_query_string(&self) -> Result<String> {
+                    Ok(crate::serde_utils::query_config().serialize_string(self)?)
+                }
+            }
+        )*
+    };
+}
+
+impl_to_query_string!(
+    SqiHistoryRequest,
+    PopularQueriesRequest,
+    QueryAnalyticsRequest,
+    QueryHistoryRequest,
+    GetSitemapsRequest,
+    GetUserSitemapsRequest,
+    IndexingHistoryRequest,
+    GetIndexingSamplesRequest,
+    GetSearchUrlsSamplesRequest,
+    GetSearchEventsSamplesRequest,
+    GetRecrawlTasksRequest,
+    BrokenLinksRequest,
+    BrokenLinkHistoryRequest,
+    ExternalLinksRequest,
+);
+
 // ============================================================================
 // User
 // ============================================================================
@@ -73,6 +155,69 @@ pub struct FullHostInfo {
     pub host_display_name: Option<String>,
 }
 
+impl HostInfo {
+    /// Returns `true` if the site URL uses HTTPS
+    pub fn is_https(&self) -> bool {
+        is_https_url(&self.ascii_host_url)
+    }
+
+    /// Returns a label for the site: the UTF-8 URL, or the ASCII one if it's empty
+    pub fn display_name(&self) -> String {
+        display_url(&self.unicode_host_url, &self.ascii_host_url).to_string()
+    }
+}
+
+impl FullHostInfo {
+    /// Returns `true` if the site URL uses HTTPS
+    pub fn is_https(&self) -> bool {
+        is_https_url(&self.ascii_host_url)
+    }
+
+    /// Returns a label for the site
+    ///
+    /// Uses the display name set in Yandex.Webmaster, then the UTF-8 URL, then the
+    /// ASCII one, skipping empty values.
+    pub fn display_name(&self) -> String {
+        match self.host_display_name.as_deref() {
+            Some(name) if !name.trim().is_empty() => name.to_string(),
+            _ => display_url(&self.unicode_host_url, &self.ascii_host_url).to_string(),
+        }
+    }
+}
+
+/// Returns the UTF-8 URL unless it's empty
+fn display_url<'a>(unicode_url: &'a str, ascii_url: &'a str) -> &'a str {
+    if unicode_url.is_empty() {
+        ascii_url
+    } else {
+        unicode_url
+    }
+}
+
+/// Returns `true` if the URL has the `https` scheme
+fn is_https_url(url: &str) -> bool {
+    url.get(..8)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"))
+}
+
+/// HTTPS migration status of a site from `check_https_migration`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HttpsMigrationStatus {
+    /// The site URL uses HTTPS
+    pub host_is_https: bool,
+    /// The main mirror uses HTTPS (same as `host_is_https` if the site is its own main mirror)
+    pub main_mirror_is_https: bool,
+    /// Diagnostics report the `MAIN_MIRROR_IS_NOT_HTTPS` problem as present
+    pub main_mirror_is_not_https_problem: bool,
+}
+
+impl HttpsMigrationStatus {
+    /// Returns `true` if the main mirror uses HTTPS and no HTTPS problem is reported
+    pub fn is_migrated(&self) -> bool {
+        self.main_mirror_is_https && !self.main_mirror_is_not_https_problem
+    }
+}
+
 /// Response from adding a new host
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct AddHostResponse {
@@ -80,6 +225,145 @@ pub struct AddHostResponse {
     pub host_id: String,
 }
 
+/// Confirmation of a bulk deletion passed to `delete_hosts`
+///
+/// States how many hosts the caller means to delete, so a list unexpectedly containing
+/// more IDs (e.g. after a filter matched every host) is rejected before any request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeleteConfirmation {
+    count: usize,
+}
+
+impl DeleteConfirmation {
+    /// Confirms the deletion of exactly `count` hosts
+    pub fn count(count: usize) -> Self {
+        Self { count }
+    }
+
+    /// Checks that the number of IDs matches the confirmed count
+    pub(crate) fn check(&self, len: usize) -> Result<()> {
+        if len != self.count {
+            return Err(YandexWebmasterError::InvalidRequest(format!(
+                "deletion of {} hosts is confirmed, but {} were passed",
+                self.count, len
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Components of a host ID such as `https:example.com:443`
+///
+/// Parsed with [`ParsedHostId::parse`] or `str::parse`, formatted back with `to_string`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParsedHostId {
+    /// Scheme, `http` or `https`
+    pub scheme: String,
+    /// Host name in ASCII (punycode for internationalized domains)
+    pub host: String,
+    /// Port
+    pub port: u16,
+}
+
+impl ParsedHostId {
+    /// Parses a host ID of the `scheme:host:port` format
+    ///
+    /// A missing port defaults to 80 for `http` and 443 for `https`. Internationalized
+    /// host names are converted to punycode.
+    pub fn parse(host_id: &str) -> Result<Self> {
+        let invalid = || YandexWebmasterError::InvalidHostId(host_id.to_string());
+
+        let (scheme, rest) = host_id.split_once(':').ok_or_else(invalid)?;
+        let scheme = scheme.to_ascii_lowercase();
+        let default_port = match scheme.as_str() {
+            "http" => 80,
+            "https" => 443,
+            _ => return Err(invalid()),
+        };
+        let (host, port) = match rest.rsplit_once(':') {
+            Some((host, port)) => (host, port.parse().map_err(|_| invalid())?),
+            None => (rest, default_port),
+        };
+
+        Ok(Self {
+            host: ascii_host(host).ok_or_else(invalid)?,
+            scheme,
+            port,
+        })
+    }
+
+    /// Builds the host ID of a site URL (e.g. `https://example.com/`)
+    pub fn from_url(url: &str) -> Result<Self> {
+        let invalid = || YandexWebmasterError::InvalidHostId(url.to_string());
+        let url = reqwest::Url::parse(url).map_err(|_| invalid())?;
+
+        Ok(Self {
+            scheme: url.scheme().to_string(),
+            host: url.host_str().ok_or_else(invalid)?.to_string(),
+            port: url.port_or_known_default().ok_or_else(invalid)?,
+        })
+    }
+
+    /// Returns `true` if the port is the default one of the scheme
+    pub fn is_default_port(&self) -> bool {
+        matches!(
+            (self.scheme.as_str(), self.port),
+            ("http", 80) | ("https", 443)
+        )
+    }
+
+    /// Returns the site URL (e.g. `https://example.com/`)
+    pub fn url(&self) -> String {
+        if self.is_default_port() {
+            format!("{}://{}/", self.scheme, self.host)
+        } else {
+            format!("{}://{}:{}/", self.scheme, self.host, self.port)
+        }
+    }
+}
+
+/// Converts the host name to lowercase ASCII, `None` if it isn't a valid host
+fn ascii_host(host: &str) -> Option<String> {
+    if host.is_empty() {
+        return None;
+    }
+    let url = reqwest::Url::parse(&format!("http://{}/", host)).ok()?;
+    url.host_str().map(str::to_string)
+}
+
+/// Checks that the host ID roughly has the `scheme:host:port` shape
+///
+/// Deliberately lenient compared to [`ParsedHostId::parse`]: it only catches values that
+/// can't be host IDs at all, such as empty strings, bare domains and site URLs.
+pub(crate) fn validate_host_id(host_id: &str) -> Result<()> {
+    let valid = host_id.split_once(':').is_some_and(|(scheme, rest)| {
+        !scheme.is_empty()
+            && scheme.chars().all(|c| c.is_ascii_alphabetic())
+            && !rest.is_empty()
+            && !rest.contains(|c: char| c.is_whitespace() || matches!(c, '/' | '?' | '#'))
+    });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(YandexWebmasterError::InvalidHostId(host_id.to_string()))
+    }
+}
+
+impl std::str::FromStr for ParsedHostId {
+    type Err = YandexWebmasterError;
+
+    fn from_str(host_id: &str) -> Result<Self> {
+        Self::parse(host_id)
+    }
+}
+
+impl fmt::Display for ParsedHostId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.scheme, self.host, self.port)
+    }
+}
+
 // ============================================================================
 // Host Verification
 // ============================================================================
@@ -100,7 +384,7 @@ pub struct HostVerificationStatusResponse {
     /// Verification type
     pub verification_type: VerificationType,
     /// Verification token (for DNS and HTML methods)
-    pub verification_uin: String,
+    pub verification_uin: VerificationToken,
     /// The verification methods applied for the given site.
     pub applicable_verifiers: Vec<ExplicitVerificationType>,
     /// The time of the last check (if verification_state isn't NONE).
@@ -117,13 +401,148 @@ pub struct HostVerificationResponse {
     /// Verification type
     pub verification_type: VerificationType,
     /// Verification token (for DNS and HTML methods)
-    pub verification_uin: String,
+    pub verification_uin: VerificationToken,
     /// The verification methods applied for the given site.
     pub applicable_verifiers: Vec<ExplicitVerificationType>,
 }
 
+impl HostVerificationResponse {
+    /// Returns the quickest applicable verification method
+    ///
+    /// Methods are preferred in the order: meta tag, HTML file, DNS record, since
+    /// DNS changes may take a long time to propagate.
+    pub fn recommended_verifier(&self) -> Option<ExplicitVerificationType> {
+        [
+            ExplicitVerificationType::MetaTag,
+            ExplicitVerificationType::HtmlFile,
+            ExplicitVerificationType::Dns,
+        ]
+        .into_iter()
+        .find(|verifier| self.applicable_verifiers.contains(verifier))
+    }
+
+    /// Returns what to place on the site for each applicable verification method
+    ///
+    /// Instructions follow the order of `applicable_verifiers`.
+    pub fn instructions(&self) -> Vec<VerificationInstruction> {
+        self.applicable_verifiers
+            .iter()
+            .map(|&method| VerificationInstruction {
+                method,
+                detail: VerificationDetail::new(method, &self.verification_uin),
+            })
+            .collect()
+    }
+}
+
+/// What to place on the site to verify the rights with the method
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VerificationInstruction {
+    /// Verification method
+    pub method: ExplicitVerificationType,
+    /// Values to place for the method
+    pub detail: VerificationDetail,
+}
+
+/// Method-specific values of a [`VerificationInstruction`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum VerificationDetail {
+    /// TXT record added to the DNS records of the domain
+    Dns {
+        /// Value of the TXT record
+        txt_value: String,
+    },
+    /// Meta tag added to the `<head>` section of the home page
+    MetaTag {
+        /// Markup of the meta tag
+        markup: String,
+    },
+    /// HTML file placed in the root directory of the site
+    HtmlFile {
+        /// Name of the file
+        file_name: String,
+        /// Content of the file
+        content: String,
+    },
+}
+
+impl VerificationDetail {
+    pub(crate) fn new(method: ExplicitVerificationType, token: &VerificationToken) -> Self {
+        match method {
+            ExplicitVerificationType::Dns => VerificationDetail::Dns {
+                txt_value: token.as_dns_txt(),
+            },
+            ExplicitVerificationType::MetaTag => VerificationDetail::MetaTag {
+                markup: token.as_meta_tag(),
+            },
+            ExplicitVerificationType::HtmlFile => VerificationDetail::HtmlFile {
+                file_name: token.as_html_filename(),
+                content: token.as_html_file_content(),
+            },
+        }
+    }
+}
+
+/// How to verify the rights to a site, returned by `add_host_auto_verify`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VerificationInstructions {
+    /// ID of the added site
+    pub host_id: String,
+    /// Recommended verification method
+    pub verification_type: ExplicitVerificationType,
+    /// Verification token
+    pub verification_uin: VerificationToken,
+    /// What to place on the site before starting the verification
+    pub detail: VerificationDetail,
+}
+
+/// Token placed on the site to verify the rights to it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct VerificationToken(pub String);
+
+impl VerificationToken {
+    /// Returns the token as is
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the value of the TXT record for DNS verification
+    pub fn as_dns_txt(&self) -> String {
+        format!("yandex-verification: {}", self.0)
+    }
+
+    /// Returns the meta tag to add to the `<head>` section of the home page
+    pub fn as_meta_tag(&self) -> String {
+        format!(
+            r#"<meta name="yandex-verification" content="{}" />"#,
+            self.0
+        )
+    }
+
+    /// Returns the name of the file to place in the root directory for HTML file verification
+    pub fn as_html_filename(&self) -> String {
+        format!("yandex_{}.html", self.0)
+    }
+
+    /// Returns the content of the file for HTML file verification
+    pub fn as_html_file_content(&self) -> String {
+        format!(
+            r#"<html><head><meta http-equiv="Content-Type" content="text/html; charset=UTF-8"></head><body>Verification: {}</body></html>"#,
+            self.0
+        )
+    }
+}
+
+impl fmt::Display for VerificationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
 /// Verification state
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum VerificationState {
     /// Not verified
@@ -150,6 +569,23 @@ pub enum ExplicitVerificationType {
     HtmlFile,
 }
 
+impl ExplicitVerificationType {
+    /// Returns the value of the `verification_type` query parameter
+    pub fn as_query_value(&self) -> &'static str {
+        match self {
+            ExplicitVerificationType::Dns => "DNS",
+            ExplicitVerificationType::MetaTag => "META_TAG",
+            ExplicitVerificationType::HtmlFile => "HTML_FILE",
+        }
+    }
+}
+
+impl fmt::Display for ExplicitVerificationType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_query_value())
+    }
+}
+
 /// Verification type
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -186,6 +622,41 @@ pub enum VerificationFailReason {
     PddVerificationCancelled,
 }
 
+impl VerificationFailReason {
+    /// Returns `true` if rechecking with the same method can succeed after fixing the site
+    ///
+    /// A missing DNS record, meta tag or file can be fixed by the user, while revoked
+    /// delegation and Yandex.Mail for Domain verification require another method.
+    pub fn is_retriable(&self) -> bool {
+        match self {
+            VerificationFailReason::DnsRecordNotFound
+            | VerificationFailReason::MetaTagNotFound
+            | VerificationFailReason::WrongHtmlPageContent => true,
+            VerificationFailReason::DelegationCancelled
+            | VerificationFailReason::PddVerificationCancelled => false,
+        }
+    }
+
+    /// Returns a hint on what the user should do to pass the verification
+    pub fn user_action(&self) -> &'static str {
+        match self {
+            VerificationFailReason::DelegationCancelled => {
+                "Ask a site owner to delegate the rights again or choose another verification method"
+            }
+            VerificationFailReason::DnsRecordNotFound => {
+                "Check the TXT record of the domain and retry once the DNS changes propagate"
+            }
+            VerificationFailReason::MetaTagNotFound => {
+                "Check that the meta tag is in the <head> section of the home page and retry"
+            }
+            VerificationFailReason::WrongHtmlPageContent => {
+                "Check the name and content of the verification file and retry"
+            }
+            VerificationFailReason::PddVerificationCancelled => "Choose another verification method",
+        }
+    }
+}
+
 /// List of verified owners
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct OwnersResponse {
@@ -199,7 +670,7 @@ pub struct Owner {
     /// User login
     pub user_login: String,
     /// Confirmation code
-    pub verification_uin: String,
+    pub verification_uin: VerificationToken,
     /// Rights verification method
     pub verification_type: VerificationType,
     /// Verification date
@@ -215,23 +686,111 @@ pub struct Owner {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct HostSummaryResponse {
     /// Site quality index
+    ///
+    /// `None` means the site isn't indexed enough to have an SQI yet.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub sqi: Option<f64>,
     /// Number of searchable pages
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::serde_utils::string_or_number")]
     pub searchable_pages_count: i64,
     /// Number of excluded pages
-    #[serde(default)]
+    #[serde(default, deserialize_with = "crate::serde_utils::string_or_number")]
     pub excluded_pages_count: i64,
     /// Site problems grouped by severity
     #[serde(default)]
     pub site_problems: HashMap<SiteProblemSeverityEnum, i32>,
 }
 
+impl HostSummaryResponse {
+    /// Returns the site quality index status
+    pub fn sqi_status(&self) -> SqiStatus {
+        match self.sqi {
+            Some(sqi) => SqiStatus::Available(sqi),
+            None => SqiStatus::NotYetComputed,
+        }
+    }
+
+    /// Returns `true` if the site quality index is computed
+    pub fn has_sqi(&self) -> bool {
+        self.sqi.is_some()
+    }
+
+    /// Returns the site quality index, or 0 if it isn't computed yet
+    pub fn sqi_or_zero(&self) -> f64 {
+        self.sqi.unwrap_or(0.0)
+    }
+
+    /// Returns the changes since the previous summary of the site
+    pub fn delta(&self, previous: &HostSummaryResponse) -> SummaryDelta {
+        let mut site_problems = HashMap::new();
+        for severity in self
+            .site_problems
+            .keys()
+            .chain(previous.site_problems.keys())
+        {
+            let count = |summary: &HostSummaryResponse| {
+                summary.site_problems.get(severity).copied().unwrap_or(0)
+            };
+            let change = count(self) - count(previous);
+            if change != 0 {
+                site_problems.insert(*severity, change);
+            }
+        }
+
+        SummaryDelta {
+            sqi_previous: previous.sqi,
+            sqi_current: self.sqi,
+            searchable_pages_count: self.searchable_pages_count - previous.searchable_pages_count,
+            excluded_pages_count: self.excluded_pages_count - previous.excluded_pages_count,
+            site_problems,
+        }
+    }
+}
+
+/// Changes between two summaries of a site from [`HostSummaryResponse::delta`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SummaryDelta {
+    /// Site quality index of the previous summary
+    pub sqi_previous: Option<f64>,
+    /// Site quality index of the current summary
+    pub sqi_current: Option<f64>,
+    /// Change of the number of searchable pages
+    pub searchable_pages_count: i64,
+    /// Change of the number of excluded pages
+    pub excluded_pages_count: i64,
+    /// Change of the number of problems by severity, severities without changes are omitted
+    pub site_problems: HashMap<SiteProblemSeverityEnum, i32>,
+}
+
+impl SummaryDelta {
+    /// Returns the change of the site quality index, `None` if either summary has no SQI
+    pub fn sqi_change(&self) -> Option<f64> {
+        Some(self.sqi_current? - self.sqi_previous?)
+    }
+
+    /// Returns `true` if nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.sqi_previous == self.sqi_current
+            && self.searchable_pages_count == 0
+            && self.excluded_pages_count == 0
+            && self.site_problems.is_empty()
+    }
+}
+
+/// Site quality index status
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SqiStatus {
+    /// Site quality index value
+    Available(f64),
+    /// The site isn't indexed enough to have a site quality index yet
+    NotYetComputed,
+}
+
 /// Excluded pages statistics by status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ExcludedPagesStatistics {
     /// Statistics by status
+    #[serde(default)]
     pub statuses: HashMap<ApiExcludedUrlStatus, i64>,
 }
 
@@ -239,14 +798,38 @@ pub struct ExcludedPagesStatistics {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default, TypedBuilder)]
 #[builder(field_defaults(default, setter(into)))]
 pub struct SqiHistoryRequest {
+    /// Date from
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     pub date_from: Option<DateTime<Utc>>,
+    /// Date to
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     pub date_to: Option<DateTime<Utc>>,
 }
 
+impl SqiHistoryRequest {
+    /// Creates a request for the last `days` days up to now
+    pub fn last_days(days: i64) -> Self {
+        let (date_from, date_to) = last_days_range(days);
+        Self {
+            date_from: Some(date_from),
+            date_to: Some(date_to),
+        }
+    }
+}
+
 /// Site quality index history
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SqiHistoryResponse {
     /// History points
+    #[serde(default)]
     pub points: Vec<SqiPoint>,
 }
 
@@ -259,6 +842,27 @@ pub struct SqiPoint {
     pub value: f64,
 }
 
+impl SqiHistoryResponse {
+    /// Returns the points sorted by date in ascending order with duplicates removed
+    ///
+    /// Points falling on the same calendar day (UTC) are considered duplicates;
+    /// only the last one in the server-provided order is kept.
+    pub fn normalized(&self) -> Vec<SqiPoint> {
+        let mut points = self.points.clone();
+        // Stable sort keeps the server order for points of the same day
+        points.sort_by_key(|p| p.date.date_naive());
+
+        let mut result: Vec<SqiPoint> = Vec::with_capacity(points.len());
+        for point in points {
+            match result.last_mut() {
+                Some(last) if last.date.date_naive() == point.date.date_naive() => *last = point,
+                _ => result.push(point),
+            }
+        }
+        result
+    }
+}
+
 // ============================================================================
 // Search Queries
 // ============================================================================
@@ -274,7 +878,7 @@ pub enum ApiQueryOrderField {
 }
 
 /// Query indicators
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ApiQueryIndicator {
     /// Total number of shows
@@ -288,7 +892,7 @@ pub enum ApiQueryIndicator {
 }
 
 /// Device type indicator
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ApiDeviceTypeIndicator {
     /// All device types
@@ -318,11 +922,19 @@ pub struct PopularQueriesRequest {
     #[builder(default, setter(into, strip_option))]
     pub device_type_indicator: Option<ApiDeviceTypeIndicator>,
     /// Start date of the range
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date"
+    )]
     #[builder(default, setter(into, strip_option))]
     pub date_from: Option<NaiveDate>,
     /// End date of the range
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date"
+    )]
     #[builder(default, setter(into, strip_option))]
     pub date_to: Option<NaiveDate>,
     /// List offset (minimum: 0, default: 0)
@@ -335,6 +947,18 @@ pub struct PopularQueriesRequest {
     pub limit: Option<i32>,
 }
 
+impl PopularQueriesRequest {
+    /// Sets the date range to the last `days` days up to today
+    pub fn with_last_days(self, days: i64) -> Self {
+        let (date_from, date_to) = last_days_date_range(days);
+        Self {
+            date_from: Some(date_from),
+            date_to: Some(date_to),
+            ..self
+        }
+    }
+}
+
 /// Popular search queries response
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PopularQueriesResponse {
@@ -345,9 +969,37 @@ pub struct PopularQueriesResponse {
     /// End date of the range
     pub date_to: NaiveDate,
     /// Total number of search queries available
+    #[serde(deserialize_with = "crate::serde_utils::string_or_number")]
     pub count: i32,
 }
 
+impl PopularQueriesResponse {
+    /// Returns the dates covered by the statistics, both ends included
+    pub fn date_range(&self) -> RangeInclusive<NaiveDate> {
+        self.date_from..=self.date_to
+    }
+
+    /// Returns the number of days covered by the statistics, both ends included
+    pub fn days_covered(&self) -> i64 {
+        (self.date_to - self.date_from).num_days() + 1
+    }
+
+    /// Returns the click-through rate of all queries together
+    ///
+    /// Computed from the total clicks and shows of the queries that have both
+    /// indicators, so queries with more shows weigh more. `None` if there are no shows.
+    pub fn average_ctr(&self) -> Option<f64> {
+        let (clicks, shows) = self
+            .queries
+            .iter()
+            .filter_map(PopularQuery::clicks_and_shows)
+            .fold((0.0, 0.0), |(clicks, shows), (c, s)| {
+                (clicks + c, shows + s)
+            });
+        (shows > 0.0).then(|| clicks / shows)
+    }
+}
+
 /// Popular query information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct PopularQuery {
@@ -359,25 +1011,97 @@ pub struct PopularQuery {
     pub indicators: std::collections::HashMap<ApiQueryIndicator, f64>,
 }
 
+impl PopularQuery {
+    /// Returns the click-through rate, `TOTAL_CLICKS / TOTAL_SHOWS`
+    ///
+    /// `None` if either indicator wasn't requested or the query has no shows.
+    pub fn ctr(&self) -> Option<f64> {
+        let (clicks, shows) = self.clicks_and_shows()?;
+        (shows > 0.0).then(|| clicks / shows)
+    }
+
+    /// Returns the total clicks and shows if both indicators are present
+    fn clicks_and_shows(&self) -> Option<(f64, f64)> {
+        Some((
+            *self.indicators.get(&ApiQueryIndicator::TotalClicks)?,
+            *self.indicators.get(&ApiQueryIndicator::TotalShows)?,
+        ))
+    }
+}
+
+/// Fails if no query indicators are requested
+///
+/// The API rejects requests without `query_indicator` with `FIELD_VALIDATION_ERROR`.
+fn validate_query_indicators(indicators: &[ApiQueryIndicator]) -> Result<()> {
+    if indicators.is_empty() {
+        return Err(YandexWebmasterError::InvalidRequest(
+            "query_indicator must contain at least one indicator".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 /// Query analytics request parameters
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TypedBuilder)]
+#[builder(build_method(into = Result<QueryAnalyticsRequest>))]
 pub struct QueryAnalyticsRequest {
     /// Indicators for displaying requests (can specify multiple)
+    #[builder(setter(into))]
     pub query_indicator: Vec<ApiQueryIndicator>,
     /// Device type indicator (default: ALL)
     #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(into, strip_option))]
     pub device_type_indicator: Option<ApiDeviceTypeIndicator>,
     /// Start date of the range
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     #[builder(default, setter(into, strip_option))]
     pub date_from: Option<DateTime<Utc>>,
     /// End date of the range
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     #[builder(default, setter(into, strip_option))]
     pub date_to: Option<DateTime<Utc>>,
 }
 
+impl From<QueryAnalyticsRequest> for Result<QueryAnalyticsRequest> {
+    /// Validates the request, so `QueryAnalyticsRequest::builder().build()` returns a `Result`
+    fn from(request: QueryAnalyticsRequest) -> Self {
+        request.validate()?;
+        Ok(request)
+    }
+}
+
+impl QueryAnalyticsRequest {
+    /// Checks the request without sending it
+    ///
+    /// `build()` of the builder and the client run the same check, so calling this is
+    /// only needed for requests created with a struct literal or modified after building.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`YandexWebmasterError::InvalidRequest`] if no indicators are requested.
+    pub fn validate(&self) -> Result<()> {
+        validate_query_indicators(&self.query_indicator)
+    }
+
+    /// Sets the date range to the last `days` days up to now
+    pub fn with_last_days(self, days: i64) -> Self {
+        let (date_from, date_to) = last_days_range(days);
+        Self {
+            date_from: Some(date_from),
+            date_to: Some(date_to),
+            ..self
+        }
+    }
+}
+
 /// Query analytics response
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct QueryAnalyticsResponse {
@@ -385,6 +1109,36 @@ pub struct QueryAnalyticsResponse {
     pub indicators: std::collections::HashMap<ApiQueryIndicator, Vec<IndicatorPoint>>,
 }
 
+impl QueryAnalyticsResponse {
+    /// Merges responses for adjacent date ranges into one
+    ///
+    /// Points of every indicator are sorted by date. If several responses contain a point
+    /// for the same date, the one from the later response in the list is kept.
+    pub fn merge(responses: Vec<QueryAnalyticsResponse>) -> QueryAnalyticsResponse {
+        QueryAnalyticsResponse {
+            indicators: merge_histories(
+                responses.into_iter().map(|response| response.indicators),
+                |point| point.date,
+            ),
+        }
+    }
+
+    /// Returns the history as `(date, indicator, value)` rows sorted by date, then indicator
+    pub fn to_long(&self) -> Vec<(DateTime<Utc>, ApiQueryIndicator, f64)> {
+        let mut rows: Vec<_> = self
+            .indicators
+            .iter()
+            .flat_map(|(indicator, points)| {
+                points
+                    .iter()
+                    .map(move |point| (point.date, *indicator, point.value))
+            })
+            .collect();
+        rows.sort_by_key(|(date, indicator, _)| (*date, *indicator));
+        rows
+    }
+}
+
 /// Single indicator history point
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct IndicatorPoint {
@@ -396,23 +1150,65 @@ pub struct IndicatorPoint {
 
 /// Query history request parameters
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TypedBuilder)]
+#[builder(build_method(into = Result<QueryHistoryRequest>))]
 pub struct QueryHistoryRequest {
     /// Indicators for displaying requests (can specify multiple)
+    #[builder(setter(into))]
     pub query_indicator: Vec<ApiQueryIndicator>,
     /// Device type indicator (default: ALL)
     #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(default, setter(into, strip_option))]
     pub device_type_indicator: Option<ApiDeviceTypeIndicator>,
     /// Start date of the range
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date"
+    )]
     #[builder(default, setter(into, strip_option))]
     pub date_from: Option<NaiveDate>,
     /// End date of the range
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date"
+    )]
     #[builder(default, setter(into, strip_option))]
     pub date_to: Option<NaiveDate>,
 }
 
+impl From<QueryHistoryRequest> for Result<QueryHistoryRequest> {
+    /// Validates the request, so `QueryHistoryRequest::builder().build()` returns a `Result`
+    fn from(request: QueryHistoryRequest) -> Self {
+        request.validate()?;
+        Ok(request)
+    }
+}
+
+impl QueryHistoryRequest {
+    /// Checks the request without sending it
+    ///
+    /// `build()` of the builder and the client run the same check, so calling this is
+    /// only needed for requests created with a struct literal or modified after building.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`YandexWebmasterError::InvalidRequest`] if no indicators are requested.
+    pub fn validate(&self) -> Result<()> {
+        validate_query_indicators(&self.query_indicator)
+    }
+
+    /// Sets the date range to the last `days` days up to today
+    pub fn with_last_days(self, days: i64) -> Self {
+        let (date_from, date_to) = last_days_date_range(days);
+        Self {
+            date_from: Some(date_from),
+            date_to: Some(date_to),
+            ..self
+        }
+    }
+}
+
 /// Query history response
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct QueryHistoryResponse {
@@ -424,12 +1220,48 @@ pub struct QueryHistoryResponse {
     pub indicators: std::collections::HashMap<ApiQueryIndicator, Vec<IndicatorPoint>>,
 }
 
+impl QueryHistoryResponse {
+    /// Flattens the history into one row per date and indicator, sorted by date
+    pub fn rows(&self) -> Vec<QueryHistoryRow> {
+        let mut rows: Vec<QueryHistoryRow> = self
+            .indicators
+            .iter()
+            .flat_map(|(&indicator, points)| {
+                points.iter().map(move |point| QueryHistoryRow {
+                    query_id: self.query_id.clone(),
+                    query_text: self.query_text.clone(),
+                    date: point.date,
+                    indicator,
+                    value: point.value,
+                })
+            })
+            .collect();
+        rows.sort_by_key(|row| (row.date, row.indicator));
+        rows
+    }
+}
+
+/// Value of a query indicator on a single date, returned by `get_query_histories_table`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QueryHistoryRow {
+    /// Search query ID
+    pub query_id: String,
+    /// Search query text
+    pub query_text: String,
+    /// Date
+    pub date: DateTime<Utc>,
+    /// Indicator
+    pub indicator: ApiQueryIndicator,
+    /// Value
+    pub value: f64,
+}
+
 // ============================================================================
 // Sitemaps
 // ============================================================================
 
 /// Source of the Sitemap file
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ApiSitemapSource {
     /// Sitemap is specified in the site's robots.txt file
@@ -469,9 +1301,31 @@ pub struct GetSitemapsRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SitemapsResponse {
     /// Sitemaps
+    #[serde(default)]
     pub sitemaps: Vec<SitemapInfo>,
 }
 
+impl SitemapsResponse {
+    /// Returns the sitemaps discovered from the source
+    pub fn filter_by_source(&self, source: ApiSitemapSource) -> Vec<&SitemapInfo> {
+        self.sitemaps
+            .iter()
+            .filter(|sitemap| sitemap.sources.contains(&source))
+            .collect()
+    }
+
+    /// Counts the sitemaps per source
+    ///
+    /// A sitemap discovered from several sources is counted for each of them.
+    pub fn sources_summary(&self) -> HashMap<ApiSitemapSource, usize> {
+        let mut summary = HashMap::new();
+        for source in self.sitemaps.iter().flat_map(|sitemap| &sitemap.sources) {
+            *summary.entry(*source).or_insert(0) += 1;
+        }
+        summary
+    }
+}
+
 /// Sitemap information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SitemapInfo {
@@ -483,10 +1337,13 @@ pub struct SitemapInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub last_access_date: Option<DateTime<Utc>>,
     /// Number of errors in the file
+    #[serde(deserialize_with = "crate::serde_utils::string_or_number")]
     pub errors_count: i32,
     /// Number of URLs in the file
+    #[serde(deserialize_with = "crate::serde_utils::string_or_number")]
     pub urls_count: i64,
     /// Number of child Sitemap files
+    #[serde(deserialize_with = "crate::serde_utils::string_or_number")]
     pub children_count: i32,
     /// Sources that led the robot to this file
     pub sources: Vec<ApiSitemapSource>,
@@ -512,6 +1369,7 @@ pub struct UserSitemapsResponse {
     /// Sitemaps
     pub sitemaps: Vec<UserSitemapInfo>,
     /// Total number of Sitemap files added by the user
+    #[serde(deserialize_with = "crate::serde_utils::string_or_number")]
     pub count: i32,
 }
 
@@ -557,8 +1415,23 @@ pub enum IndexingStatusEnum {
     Other,
 }
 
+impl IndexingStatusEnum {
+    /// Returns the bucket of the HTTP status code
+    pub fn from_http_code(code: u16) -> Self {
+        match code {
+            200..=299 => Self::Http2xx,
+            300..=399 => Self::Http3xx,
+            400..=499 => Self::Http4xx,
+            500..=599 => Self::Http5xx,
+            _ => Self::Other,
+        }
+    }
+}
+
 /// Site problem severity
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+///
+/// Ordered from the most severe ([`Self::Fatal`]) to the least severe.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum SiteProblemSeverityEnum {
     /// Fatal problems
@@ -635,6 +1508,108 @@ pub enum ApiExcludedUrlStatus {
     Other,
 }
 
+impl ApiExcludedUrlStatus {
+    /// Returns `true` if the status is a legacy code that the API no longer emits
+    /// for new exclusions but may still return for historical data.
+    pub fn is_legacy(&self) -> bool {
+        match self {
+            ApiExcludedUrlStatus::NothingFound
+            | ApiExcludedUrlStatus::HostError
+            | ApiExcludedUrlStatus::RedirectNotsearchable
+            | ApiExcludedUrlStatus::HttpError
+            | ApiExcludedUrlStatus::NotCanonical
+            | ApiExcludedUrlStatus::NotMainMirror
+            | ApiExcludedUrlStatus::ParserError
+            | ApiExcludedUrlStatus::RobotsHostError
+            | ApiExcludedUrlStatus::RobotsUrlError
+            | ApiExcludedUrlStatus::Duplicate
+            | ApiExcludedUrlStatus::CleanParams
+            | ApiExcludedUrlStatus::NoIndex
+            | ApiExcludedUrlStatus::LowQuality
+            | ApiExcludedUrlStatus::Other => false,
+
+            ApiExcludedUrlStatus::ForbiddenByRobotsTxt
+            | ApiExcludedUrlStatus::UrlNotAllowed
+            | ApiExcludedUrlStatus::ContainsNoindexMetaTag
+            | ApiExcludedUrlStatus::ContainsNoindexXRobotsTagHeader
+            | ApiExcludedUrlStatus::SitemapForbidden
+            | ApiExcludedUrlStatus::SitemapNotAllowed
+            | ApiExcludedUrlStatus::AlternativeDuplicate
+            | ApiExcludedUrlStatus::UserDuplicate
+            | ApiExcludedUrlStatus::CanonicalDuplicate
+            | ApiExcludedUrlStatus::RedirectDuplicate
+            | ApiExcludedUrlStatus::MovedPermanently
+            | ApiExcludedUrlStatus::MovedTemporarily
+            | ApiExcludedUrlStatus::MalwareDetected
+            | ApiExcludedUrlStatus::PhishingDetected
+            | ApiExcludedUrlStatus::AdultContent => true,
+        }
+    }
+
+    /// Returns `true` if the status is one of the codes currently documented by the API
+    pub fn is_current(&self) -> bool {
+        !self.is_legacy()
+    }
+
+    /// Returns a short lowercase description of the reason, e.g. for UI messages
+    pub fn description(&self) -> &'static str {
+        match self {
+            ApiExcludedUrlStatus::NothingFound => {
+                "the robot doesn't know about the page or it was unavailable"
+            }
+            ApiExcludedUrlStatus::HostError => "the robot couldn't connect to the server",
+            ApiExcludedUrlStatus::RedirectNotsearchable => "the page redirects to another page",
+            ApiExcludedUrlStatus::HttpError => "the page responded with an HTTP error",
+            ApiExcludedUrlStatus::NotCanonical => "the page is indexed by its canonical URL",
+            ApiExcludedUrlStatus::NotMainMirror => "the page belongs to a secondary site mirror",
+            ApiExcludedUrlStatus::ParserError => "the robot couldn't get the page content",
+            ApiExcludedUrlStatus::RobotsHostError => "site indexing is prohibited in robots.txt",
+            ApiExcludedUrlStatus::RobotsUrlError => "page indexing is prohibited in robots.txt",
+            ApiExcludedUrlStatus::Duplicate => "the page duplicates a page already in search",
+            ApiExcludedUrlStatus::CleanParams => "the page is excluded by a Clean-param directive",
+            ApiExcludedUrlStatus::NoIndex => "the robots meta tag has the noindex value",
+            ApiExcludedUrlStatus::ForbiddenByRobotsTxt => "the page is forbidden by robots.txt",
+            ApiExcludedUrlStatus::UrlNotAllowed => "the URL is not allowed",
+            ApiExcludedUrlStatus::ContainsNoindexMetaTag => "the page has a noindex meta tag",
+            ApiExcludedUrlStatus::ContainsNoindexXRobotsTagHeader => {
+                "the page has a noindex X-Robots-Tag header"
+            }
+            ApiExcludedUrlStatus::SitemapForbidden => "the sitemap is forbidden",
+            ApiExcludedUrlStatus::SitemapNotAllowed => "the sitemap is not allowed",
+            ApiExcludedUrlStatus::LowQuality => "the page is considered low quality",
+            ApiExcludedUrlStatus::AlternativeDuplicate => "the page is an alternative duplicate",
+            ApiExcludedUrlStatus::UserDuplicate => "the page is a duplicate set by the user",
+            ApiExcludedUrlStatus::CanonicalDuplicate => "the page duplicates its canonical URL",
+            ApiExcludedUrlStatus::RedirectDuplicate => "the page duplicates its redirect target",
+            ApiExcludedUrlStatus::MovedPermanently => "the page has moved permanently",
+            ApiExcludedUrlStatus::MovedTemporarily => "the page has moved temporarily",
+            ApiExcludedUrlStatus::MalwareDetected => "malware was detected on the page",
+            ApiExcludedUrlStatus::PhishingDetected => "phishing was detected on the page",
+            ApiExcludedUrlStatus::AdultContent => "the page has adult content",
+            ApiExcludedUrlStatus::Other => "the robot doesn't have updated data",
+        }
+    }
+}
+
+/// Describes why a page is out of search from the fields shared by search samples
+fn exclusion_reason(
+    status: Option<ApiExcludedUrlStatus>,
+    bad_http_status: Option<i32>,
+    target_url: Option<&str>,
+) -> Option<String> {
+    let mut reason = match (status, bad_http_status) {
+        (Some(ApiExcludedUrlStatus::HttpError) | None, Some(code)) => {
+            format!("the page responded with HTTP {code}")
+        }
+        (Some(status), _) => status.description().to_string(),
+        (None, None) => return None,
+    };
+    if let Some(target_url) = target_url {
+        reason.push_str(&format!(" (target: {target_url})"));
+    }
+    Some(reason)
+}
+
 /// Important URL change indicator
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
@@ -654,20 +1629,98 @@ pub enum ApiImportantUrlChangeIndicator {
 #[builder(field_defaults(default, setter(into)))]
 pub struct IndexingHistoryRequest {
     /// Date from
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     pub date_from: Option<DateTime<Utc>>,
     /// Date to
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     pub date_to: Option<DateTime<Utc>>,
 }
 
+impl IndexingHistoryRequest {
+    /// Creates a request for the last `days` days up to now
+    pub fn last_days(days: i64) -> Self {
+        let (date_from, date_to) = last_days_range(days);
+        Self {
+            date_from: Some(date_from),
+            date_to: Some(date_to),
+        }
+    }
+}
+
 /// Indexing history response
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct IndexingHistoryResponse {
     /// History indicators by status
+    #[serde(default)]
     pub indicators: HashMap<IndexingStatusEnum, Vec<IndexingHistoryPoint>>,
 }
 
+impl IndexingHistoryResponse {
+    /// Merges histories of several date ranges into one
+    ///
+    /// Points of every status are sorted by date in ascending order. Points with the same
+    /// date are considered duplicates from overlapping ranges; the one from the latest
+    /// response in `responses` is kept.
+    pub fn merge(responses: Vec<IndexingHistoryResponse>) -> IndexingHistoryResponse {
+        IndexingHistoryResponse {
+            indicators: merge_histories(
+                responses.into_iter().map(|response| response.indicators),
+                |point| point.date,
+            ),
+        }
+    }
+
+    /// Returns one row per date with the values of all statuses, sorted by date
+    ///
+    /// Statuses without a point for the date are zero.
+    pub fn pivot(&self) -> Vec<IndexingDayRow> {
+        let mut rows: BTreeMap<DateTime<Utc>, IndexingDayRow> = BTreeMap::new();
+        for (status, points) in &self.indicators {
+            for point in points {
+                let row = rows.entry(point.date).or_insert_with(|| IndexingDayRow {
+                    date: point.date,
+                    ..IndexingDayRow::default()
+                });
+                let value = match status {
+                    IndexingStatusEnum::Http2xx => &mut row.http_2xx,
+                    IndexingStatusEnum::Http3xx => &mut row.http_3xx,
+                    IndexingStatusEnum::Http4xx => &mut row.http_4xx,
+                    IndexingStatusEnum::Http5xx => &mut row.http_5xx,
+                    IndexingStatusEnum::Other => &mut row.other,
+                };
+                *value = point.value;
+            }
+        }
+
+        rows.into_values().collect()
+    }
+}
+
+/// Indexing history of a single date with the values of all statuses
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+pub struct IndexingDayRow {
+    /// Date
+    pub date: DateTime<Utc>,
+    /// Pages with HTTP 2xx responses
+    pub http_2xx: f64,
+    /// Pages with HTTP 3xx responses
+    pub http_3xx: f64,
+    /// Pages with HTTP 4xx responses
+    pub http_4xx: f64,
+    /// Pages with HTTP 5xx responses
+    pub http_5xx: f64,
+    /// Pages with other statuses
+    pub other: f64,
+}
+
 /// Indexing history point
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct IndexingHistoryPoint {
@@ -693,8 +1746,10 @@ pub struct GetIndexingSamplesRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct IndexingSamplesResponse {
     /// Sample URLs
+    #[serde(default)]
     pub samples: Vec<IndexingSample>,
     /// Total count
+    #[serde(default, deserialize_with = "crate::serde_utils::string_or_number")]
     pub count: i32,
 }
 
@@ -704,6 +1759,7 @@ pub struct IndexingSample {
     /// URL
     pub url: String,
     /// HTTP status code
+    #[serde(deserialize_with = "crate::serde_utils::string_or_number")]
     pub http_code: i32,
     /// Last access date
     pub access_date: DateTime<Utc>,
@@ -727,6 +1783,7 @@ pub enum ApiSearchEventEnum {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SearchUrlsHistoryResponse {
     /// History points
+    #[serde(default)]
     pub history: Vec<SearchUrlsHistoryPoint>,
 }
 
@@ -755,8 +1812,10 @@ pub struct GetSearchUrlsSamplesRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SearchUrlsSamplesResponse {
     /// Total number of available examples
+    #[serde(default, deserialize_with = "crate::serde_utils::string_or_number")]
     pub count: i32,
     /// Sample pages
+    #[serde(default)]
     pub samples: Vec<SearchUrlsSample>,
 }
 
@@ -775,6 +1834,7 @@ pub struct SearchUrlsSample {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SearchEventsHistoryResponse {
     /// History indicators by event type
+    #[serde(default)]
     pub indicators: HashMap<ApiSearchEventEnum, Vec<SearchUrlsHistoryPoint>>,
 }
 
@@ -788,14 +1848,19 @@ pub struct GetSearchEventsSamplesRequest {
     /// Limit for pagination (1-100, default 50)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<i32>,
+    /// Return only events of this type (default: both appearances and removals)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<ApiSearchEventEnum>,
 }
 
 /// Search events samples response
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SearchEventsSamplesResponse {
     /// Total number of available examples
+    #[serde(default, deserialize_with = "crate::serde_utils::string_or_number")]
     pub count: i32,
     /// Sample pages
+    #[serde(default)]
     pub samples: Vec<SearchEventsSample>,
 }
 
@@ -823,6 +1888,24 @@ pub struct SearchEventsSample {
     pub target_url: Option<String>,
 }
 
+impl SearchEventsSample {
+    /// Explains the event in a sentence, e.g. "The page was removed from search:
+    /// the page responded with HTTP 404."
+    pub fn reason_summary(&self) -> String {
+        match self.event {
+            ApiSearchEventEnum::AppearedInSearch => "The page appeared in search.".to_string(),
+            ApiSearchEventEnum::RemovedFromSearch => match exclusion_reason(
+                self.excluded_url_status,
+                self.bad_http_status,
+                self.target_url.as_deref(),
+            ) {
+                Some(reason) => format!("The page was removed from search: {reason}."),
+                None => "The page was removed from search.".to_string(),
+            },
+        }
+    }
+}
+
 // ============================================================================
 // Recrawl (Reindexing)
 // ============================================================================
@@ -845,13 +1928,33 @@ pub struct GetRecrawlTasksRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub limit: Option<i32>,
     /// Start of the date range
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     pub date_from: Option<DateTime<Utc>>,
     /// End of the date range
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     pub date_to: Option<DateTime<Utc>>,
 }
 
+impl GetRecrawlTasksRequest {
+    /// Creates a request for the last `days` days up to now
+    pub fn last_days(days: i64) -> Self {
+        let (date_from, date_to) = last_days_range(days);
+        Self {
+            date_from: Some(date_from),
+            date_to: Some(date_to),
+            ..Default::default()
+        }
+    }
+}
+
 /// Recrawl task list response
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RecrawlTasksResponse {
@@ -859,6 +1962,16 @@ pub struct RecrawlTasksResponse {
     pub tasks: Vec<RecrawlTask>,
 }
 
+impl RecrawlTasksResponse {
+    /// Returns the tasks in the state
+    pub fn by_state(&self, state: RecrawlTaskState) -> Vec<&RecrawlTask> {
+        self.tasks
+            .iter()
+            .filter(|task| task.state == state)
+            .collect()
+    }
+}
+
 /// Recrawl task information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct RecrawlTask {
@@ -894,6 +2007,18 @@ pub struct RecrawlQuotaResponse {
     pub quota_remainder: i32,
 }
 
+/// Rate limit state of the client from `check_rate_limit`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RateLimitStatus {
+    /// No request was rejected by the rate limit recently
+    Available,
+    /// A request was rejected by the rate limit, requests fail until the delay passes
+    Exhausted {
+        /// Time left until the limit resets
+        retry_after: std::time::Duration,
+    },
+}
+
 // ============================================================================
 // Links
 // ============================================================================
@@ -929,8 +2054,10 @@ pub struct BrokenLinksRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct BrokenLinksResponse {
     /// The number of example links available
+    #[serde(default, deserialize_with = "crate::serde_utils::string_or_number")]
     pub count: i32,
     /// The URL of the page that contains the link to the site
+    #[serde(default)]
     pub links: Vec<BrokenLink>,
 }
 
@@ -952,13 +2079,32 @@ pub struct BrokenLink {
 #[builder(field_defaults(default, setter(into)))]
 pub struct BrokenLinkHistoryRequest {
     /// Date from
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     pub date_from: Option<DateTime<Utc>>,
     /// Date to
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "crate::serde_utils::option_query_date_time"
+    )]
     pub date_to: Option<DateTime<Utc>>,
 }
 
+impl BrokenLinkHistoryRequest {
+    /// Creates a request for the last `days` days up to now
+    pub fn last_days(days: i64) -> Self {
+        let (date_from, date_to) = last_days_range(days);
+        Self {
+            date_from: Some(date_from),
+            date_to: Some(date_to),
+        }
+    }
+}
+
 /// Broken link history point
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct BrokenLinkHistoryPoint {
@@ -972,6 +2118,7 @@ pub struct BrokenLinkHistoryPoint {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct BrokenLinkHistoryResponse {
     /// The reason the link doesn't work
+    #[serde(default)]
     pub indicators: HashMap<ApiInternalLinksBrokenIndicator, Vec<BrokenLinkHistoryPoint>>,
 }
 
@@ -991,8 +2138,10 @@ pub struct ExternalLinksRequest {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct ExternalLinksResponse {
     /// The number of example links available
+    #[serde(default, deserialize_with = "crate::serde_utils::string_or_number")]
     pub count: i32,
     /// Samples
+    #[serde(default)]
     pub links: Vec<ExternalLink>,
 }
 
@@ -1021,6 +2170,7 @@ pub enum ApiExternalLinksIndicator {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ExternalLinksHistoryResponse {
     /// History indicators by status
+    #[serde(default)]
     pub indicators: HashMap<ApiExternalLinksIndicator, Vec<ExternalLinksHistoryPoint>>,
 }
 
@@ -1038,7 +2188,7 @@ pub struct ExternalLinksHistoryPoint {
 // ============================================================================
 
 /// Site problem type
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum ApiSiteProblemTypeEnum {
     // FATAL
@@ -1091,6 +2241,9 @@ pub enum ApiSiteProblemTypeEnum {
     /// No Yandex.Metrica counter linked to site
     NoMetrikaCounterBinding,
     /// Site crawling using Yandex.Metrica counters not enabled
+    ///
+    /// The API has no endpoint for crawl settings, the crawling by counters is enabled
+    /// in the indexing settings of the Webmaster web interface.
     NoMetrikaCounterCrawlEnabled,
     /// robots.txt file not found
     NoRobotsTxt,
@@ -1145,9 +2298,41 @@ pub enum ApiSiteProblemState {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct DiagnosticsResponse {
     /// Problems by type
+    #[serde(default)]
     pub problems: HashMap<ApiSiteProblemTypeEnum, SiteProblemInfo>,
 }
 
+impl DiagnosticsResponse {
+    /// Returns the problems as a flat list sorted by severity (most severe first), then by type
+    pub fn to_list(&self) -> Vec<DiagnosticProblem> {
+        let mut problems: Vec<DiagnosticProblem> = self
+            .problems
+            .iter()
+            .map(|(problem_type, info)| DiagnosticProblem {
+                problem_type: *problem_type,
+                severity: info.severity,
+                state: info.state,
+                last_state_update: info.last_state_update,
+            })
+            .collect();
+        problems.sort_by_key(|p| (p.severity, p.problem_type));
+        problems
+    }
+}
+
+/// Site problem with its type, from [`DiagnosticsResponse::to_list`]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiagnosticProblem {
+    /// Problem type
+    pub problem_type: ApiSiteProblemTypeEnum,
+    /// Issue type (severity)
+    pub severity: SiteProblemSeverityEnum,
+    /// State of the issue
+    pub state: ApiSiteProblemState,
+    /// Date the issue status was last changed
+    pub last_state_update: Option<DateTime<Utc>>,
+}
+
 /// Site problem information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SiteProblemInfo {
@@ -1159,6 +2344,124 @@ pub struct SiteProblemInfo {
     pub last_state_update: Option<DateTime<Utc>>,
 }
 
+// ============================================================================
+// Feeds
+// ============================================================================
+
+/// Feed type
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FeedType {
+    /// Products (e-commerce)
+    Goods,
+    /// Services
+    Services,
+    /// Real estate
+    Realty,
+    /// Vacancies
+    Vacancies,
+    /// Cars
+    Cars,
+    /// Tickets
+    Tickets,
+    /// Feed type not known to this crate
+    #[serde(other)]
+    Other,
+}
+
+/// Feed list response
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeedsResponse {
+    /// Feeds added to the site
+    pub feeds: Vec<FeedInfo>,
+}
+
+/// Feed information
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeedInfo {
+    /// Feed URL
+    pub url: String,
+    /// Feed type
+    #[serde(rename = "type")]
+    pub feed_type: FeedType,
+    /// IDs of the regions the feed is used in
+    #[serde(rename = "regionIds", default)]
+    pub region_ids: Vec<i64>,
+}
+
+/// Add feed request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TypedBuilder)]
+pub struct AddFeedRequest {
+    /// Feed URL
+    #[builder(setter(into))]
+    pub url: String,
+    /// Feed type
+    #[serde(rename = "type")]
+    pub feed_type: FeedType,
+    /// IDs of the regions the feed is used in
+    #[serde(rename = "regionIds")]
+    #[builder(default, setter(into))]
+    pub region_ids: Vec<i64>,
+}
+
+/// Response from add feed request
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AddFeedResponse {
+    /// ID of the request, used to check the result with `get_feed_add_status`
+    #[serde(rename = "requestId")]
+    pub request_id: String,
+}
+
+/// Status of a feed add request
+///
+/// Failed statuses carry the same names as the corresponding [`crate::YandexErrorCode`]s.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FeedStatus {
+    /// Feed is being checked
+    InProgress,
+    /// Feed is added
+    Ok,
+    /// Feed URL responded with a non-200 HTTP code
+    BadHttpCode,
+    /// Feed URL responded with an unsupported content type
+    BadMimeType,
+    /// Feed URL didn't respond in time
+    TimedOut,
+    /// Feed is already added to the site
+    FeedAlreadyAdded,
+    /// Only HTTPS feed URLs are accepted
+    OnlyHttps,
+    /// Feed URL is invalid
+    IncorrectUrl,
+    /// Feeds of this category are banned for the site
+    FeedsCategoryBan,
+    /// Maximum number of feeds for the site is reached
+    FeedsLimitExceeded,
+    /// Status not known to this crate, treated as a finished check that failed
+    #[serde(other)]
+    Unknown,
+}
+
+impl FeedStatus {
+    /// Returns `true` if the feed is no longer being checked
+    pub fn is_finished(&self) -> bool {
+        !matches!(self, FeedStatus::InProgress)
+    }
+
+    /// Returns `true` if the feed is added, including the case it was added before
+    pub fn is_added(&self) -> bool {
+        matches!(self, FeedStatus::Ok | FeedStatus::FeedAlreadyAdded)
+    }
+}
+
+/// Feed add request status response
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeedAddInfo {
+    /// Status of the request
+    pub status: FeedStatus,
+}
+
 // ============================================================================
 // Important URLs
 // ============================================================================
@@ -1167,9 +2470,31 @@ pub struct SiteProblemInfo {
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ImportantUrlsResponse {
     /// URLs
+    #[serde(default)]
     pub urls: Vec<ImportantUrl>,
 }
 
+impl ImportantUrlsResponse {
+    /// Returns the URLs where the given aspect changed since the previous check
+    ///
+    /// E.g. [`ApiImportantUrlChangeIndicator::SearchStatus`] gives the pages to alert on
+    /// when they appear in or drop out of search.
+    pub fn filter_changed(&self, indicator: ApiImportantUrlChangeIndicator) -> Vec<&ImportantUrl> {
+        self.urls
+            .iter()
+            .filter(|url| url.change_indicators.contains(&indicator))
+            .collect()
+    }
+
+    /// Returns the URLs with at least one change since the previous check
+    pub fn with_any_change(&self) -> Vec<&ImportantUrl> {
+        self.urls
+            .iter()
+            .filter(|url| !url.change_indicators.is_empty())
+            .collect()
+    }
+}
+
 /// Important URL information
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ImportantUrl {
@@ -1195,12 +2520,40 @@ pub struct IndexingStatus {
     /// Generalized status of the HTTP code
     pub status: IndexingStatusEnum,
     /// HTTP code
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::serde_utils::option_string_or_number"
+    )]
     pub http_code: Option<i32>,
     /// Date the page was crawled
     pub access_date: DateTime<Utc>,
 }
 
+impl IndexingStatus {
+    /// Returns the bucket of the exact HTTP code if present, otherwise the reported one
+    fn bucket(&self) -> IndexingStatusEnum {
+        self.http_code
+            .and_then(|code| u16::try_from(code).ok())
+            .map_or(self.status, IndexingStatusEnum::from_http_code)
+    }
+
+    /// Returns `true` if the page responded with a 2xx code
+    pub fn is_ok(&self) -> bool {
+        self.bucket() == IndexingStatusEnum::Http2xx
+    }
+
+    /// Returns `true` if the page responded with a 4xx code
+    pub fn is_client_error(&self) -> bool {
+        self.bucket() == IndexingStatusEnum::Http4xx
+    }
+
+    /// Returns `true` if the page responded with a 5xx code
+    pub fn is_server_error(&self) -> bool {
+        self.bucket() == IndexingStatusEnum::Http5xx
+    }
+}
+
 /// Page search status
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct SearchStatus {
@@ -1224,9 +2577,951 @@ pub struct SearchStatus {
     pub target_url: Option<String>,
 }
 
+impl SearchStatus {
+    /// Explains the search status in a sentence, e.g. `The page is excluded from search:
+    /// the page duplicates a page already in search (target: https://example.com/).`
+    pub fn reason_summary(&self) -> String {
+        if self.searchable {
+            return "The page is in search.".to_string();
+        }
+
+        match exclusion_reason(
+            self.excluded_url_status,
+            self.bad_http_status,
+            self.target_url.as_deref(),
+        ) {
+            Some(reason) => format!("The page is excluded from search: {reason}."),
+            None => "The page is excluded from search.".to_string(),
+        }
+    }
+}
+
 /// Important URL history response
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ImportantUrlHistoryResponse {
     /// History of changes to the page
     pub history: Vec<ImportantUrl>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_excluded_url_status_legacy() {
+        assert!(ApiExcludedUrlStatus::ForbiddenByRobotsTxt.is_legacy());
+        assert!(ApiExcludedUrlStatus::AdultContent.is_legacy());
+        assert!(!ApiExcludedUrlStatus::AdultContent.is_current());
+
+        assert!(ApiExcludedUrlStatus::NoIndex.is_current());
+        assert!(ApiExcludedUrlStatus::LowQuality.is_current());
+        assert!(!ApiExcludedUrlStatus::Duplicate.is_legacy());
+    }
+
+    #[test]
+    fn test_search_events_samples_request_event_filter() {
+        let request = GetSearchEventsSamplesRequest::builder()
+            .event(ApiSearchEventEnum::RemovedFromSearch)
+            .build();
+        assert_eq!(
+            serde_qs::to_string(&request).unwrap(),
+            "event=REMOVED_FROM_SEARCH"
+        );
+
+        let request = GetSearchEventsSamplesRequest::default();
+        assert_eq!(serde_qs::to_string(&request).unwrap(), "");
+    }
+
+    #[test]
+    fn test_sqi_history_normalized() {
+        let json = r#"{
+            "points": [
+                { "date": "2024-01-03T00:00:00Z", "value": 30.0 },
+                { "date": "2024-01-01T00:00:00Z", "value": 10.0 },
+                { "date": "2024-01-03T12:00:00Z", "value": 35.0 },
+                { "date": "2024-01-02T00:00:00Z", "value": 20.0 }
+            ]
+        }"#;
+
+        let response: SqiHistoryResponse = serde_json::from_str(json).unwrap();
+        let values: Vec<f64> = response.normalized().iter().map(|p| p.value).collect();
+        assert_eq!(values, vec![10.0, 20.0, 35.0]);
+    }
+
+    #[test]
+    fn test_feeds() {
+        let request = AddFeedRequest::builder()
+            .url("https://example.com/feed.xml")
+            .feed_type(FeedType::Goods)
+            .region_ids(vec![213])
+            .build();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap(),
+            serde_json::json!({
+                "url": "https://example.com/feed.xml",
+                "type": "GOODS",
+                "regionIds": [213]
+            })
+        );
+
+        let response: FeedsResponse = serde_json::from_str(
+            r#"{ "feeds": [{ "url": "https://example.com/feed.xml", "type": "NEW_TYPE" }] }"#,
+        )
+        .unwrap();
+        assert_eq!(response.feeds[0].feed_type, FeedType::Other);
+        assert!(response.feeds[0].region_ids.is_empty());
+
+        let info: FeedAddInfo = serde_json::from_str(r#"{ "status": "BAD_MIME_TYPE" }"#).unwrap();
+        assert!(info.status.is_finished());
+        assert!(!info.status.is_added());
+        assert!(FeedStatus::FeedAlreadyAdded.is_added());
+        assert!(!FeedStatus::InProgress.is_finished());
+
+        let info: FeedAddInfo = serde_json::from_str(r#"{ "status": "NEW_STATUS" }"#).unwrap();
+        assert_eq!(info.status, FeedStatus::Unknown);
+        assert!(info.status.is_finished());
+        assert!(!info.status.is_added());
+    }
+
+    #[test]
+    fn test_important_urls_filter_changed() {
+        let json = r#"{
+            "urls": [
+                { "url": "https://example.com/a", "change_indicators": ["SEARCH_STATUS", "TITLE"] },
+                { "url": "https://example.com/b", "change_indicators": ["TITLE"] },
+                { "url": "https://example.com/c" }
+            ]
+        }"#;
+
+        let response: ImportantUrlsResponse = serde_json::from_str(json).unwrap();
+        let urls = |urls: Vec<&ImportantUrl>| -> Vec<String> {
+            urls.into_iter().map(|u| u.url.clone()).collect()
+        };
+
+        assert_eq!(
+            urls(response.filter_changed(ApiImportantUrlChangeIndicator::SearchStatus)),
+            vec!["https://example.com/a"]
+        );
+        assert_eq!(
+            urls(response.filter_changed(ApiImportantUrlChangeIndicator::Title)).len(),
+            2
+        );
+        assert_eq!(urls(response.with_any_change()).len(), 2);
+    }
+
+    #[test]
+    fn test_host_summary_sqi() {
+        let summary: HostSummaryResponse = serde_json::from_str(r#"{ "sqi": 20.0 }"#).unwrap();
+        assert!(summary.has_sqi());
+        assert_eq!(summary.sqi_or_zero(), 20.0);
+        assert_eq!(summary.sqi_status(), SqiStatus::Available(20.0));
+
+        let summary: HostSummaryResponse = serde_json::from_str("{}").unwrap();
+        assert!(!summary.has_sqi());
+        assert_eq!(summary.sqi_or_zero(), 0.0);
+        assert_eq!(summary.sqi_status(), SqiStatus::NotYetComputed);
+    }
+
+    #[test]
+    fn test_query_date_format() {
+        let request = PopularQueriesRequest::builder()
+            .order_by(ApiQueryOrderField::TotalShows)
+            .date_from(NaiveDate::from_ymd_opt(2024, 1, 5).unwrap())
+            .date_to(NaiveDate::from_ymd_opt(2024, 2, 10).unwrap())
+            .build();
+        assert_eq!(
+            serde_qs::to_string(&request).unwrap(),
+            "order_by=TOTAL_SHOWS&date_from=2024-01-05&date_to=2024-02-10"
+        );
+
+        let request = QueryHistoryRequest::builder()
+            .query_indicator(vec![ApiQueryIndicator::TotalShows])
+            .date_from(NaiveDate::from_ymd_opt(2024, 1, 5).unwrap())
+            .build()
+            .unwrap();
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            serde_json::from_str::<QueryHistoryRequest>(&json).unwrap(),
+            request
+        );
+    }
+
+    #[test]
+    fn test_query_date_time_format() {
+        fn query<T: Serialize>(request: &T) -> String {
+            let query = crate::serde_utils::query_config()
+                .serialize_string(request)
+                .unwrap();
+            urlencoding::decode(&query).unwrap().into_owned()
+        }
+        fn roundtrip<T: Serialize + serde::de::DeserializeOwned + PartialEq + std::fmt::Debug>(
+            request: T,
+            expected: &str,
+        ) {
+            let query = query(&request);
+            assert_eq!(query, expected);
+            let parsed: T = crate::serde_utils::query_config()
+                .deserialize_str(&query)
+                .unwrap();
+            assert_eq!(parsed, request);
+        }
+
+        let from = "2024-01-05T00:00:00Z".parse::<DateTime<Utc>>().unwrap();
+        let to = "2024-02-10T12:30:15Z".parse::<DateTime<Utc>>().unwrap();
+        let range = "date_from=2024-01-05T00:00:00Z&date_to=2024-02-10T12:30:15Z";
+
+        roundtrip(
+            SqiHistoryRequest::builder()
+                .date_from(from)
+                .date_to(to)
+                .build(),
+            range,
+        );
+        roundtrip(
+            IndexingHistoryRequest::builder()
+                .date_from(from)
+                .date_to(to)
+                .build(),
+            range,
+        );
+        roundtrip(
+            GetRecrawlTasksRequest::builder()
+                .limit(10)
+                .date_from(from)
+                .date_to(to)
+                .build(),
+            &format!("limit=10&{range}"),
+        );
+        roundtrip(
+            BrokenLinkHistoryRequest::builder()
+                .date_from(from)
+                .date_to(to)
+                .build(),
+            range,
+        );
+
+        let request = QueryAnalyticsRequest::builder()
+            .query_indicator(vec![ApiQueryIndicator::TotalShows])
+            .date_from(from)
+            .date_to(to)
+            .build()
+            .unwrap();
+        assert_eq!(
+            query(&request),
+            format!("query_indicator=TOTAL_SHOWS&{range}")
+        );
+        let json = serde_json::to_string(&request).unwrap();
+        assert_eq!(
+            serde_json::from_str::<QueryAnalyticsRequest>(&json).unwrap(),
+            request
+        );
+
+        // Fractions of seconds are dropped
+        let request = IndexingHistoryRequest::builder()
+            .date_from(from + chrono::Duration::milliseconds(250))
+            .build();
+        assert_eq!(query(&request), "date_from=2024-01-05T00:00:00Z");
+    }
+
+    #[test]
+    fn test_explicit_verification_type_query_value() {
+        for verification_type in [
+            ExplicitVerificationType::Dns,
+            ExplicitVerificationType::MetaTag,
+            ExplicitVerificationType::HtmlFile,
+        ] {
+            assert_eq!(
+                serde_json::to_value(verification_type).unwrap(),
+                verification_type.as_query_value()
+            );
+        }
+        assert_eq!(ExplicitVerificationType::MetaTag.to_string(), "META_TAG");
+    }
+
+    #[test]
+    fn test_diagnostics_to_list() {
+        let json = r#"{
+            "problems": {
+                "NO_SITEMAPS": { "severity": "POSSIBLE_PROBLEM", "state": "PRESENT" },
+                "DNS_ERROR": { "severity": "FATAL", "state": "ABSENT" },
+                "CONNECT_FAILED": { "severity": "FATAL", "state": "PRESENT" },
+                "NO_METRIKA_COUNTER": { "severity": "RECOMMENDATION", "state": "UNDEFINED" }
+            }
+        }"#;
+
+        let response: DiagnosticsResponse = serde_json::from_str(json).unwrap();
+        let types: Vec<ApiSiteProblemTypeEnum> =
+            response.to_list().iter().map(|p| p.problem_type).collect();
+
+        assert_eq!(
+            types,
+            vec![
+                ApiSiteProblemTypeEnum::ConnectFailed,
+                ApiSiteProblemTypeEnum::DnsError,
+                ApiSiteProblemTypeEnum::NoSitemaps,
+                ApiSiteProblemTypeEnum::NoMetrikaCounter,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_last_days() {
+        let request = SqiHistoryRequest::last_days(7);
+        let (date_from, date_to) = (request.date_from.unwrap(), request.date_to.unwrap());
+        assert_eq!(date_to - date_from, Duration::days(7));
+
+        let request = PopularQueriesRequest::builder()
+            .order_by(ApiQueryOrderField::TotalShows)
+            .limit(10)
+            .build()
+            .with_last_days(30);
+        assert_eq!(
+            request.date_to.unwrap() - request.date_from.unwrap(),
+            Duration::days(30)
+        );
+        assert_eq!(request.limit, Some(10));
+    }
+
+    #[test]
+    fn test_host_is_https() {
+        let host: HostInfo = serde_json::from_str(
+            r#"{
+                "host_id": "http:ya.ru:80",
+                "ascii_host_url": "http://ya.ru/",
+                "unicode_host_url": "http://ya.ru/",
+                "verified": true,
+                "main_mirror": {
+                    "host_id": "https:ya.ru:443",
+                    "ascii_host_url": "HTTPS://ya.ru/",
+                    "unicode_host_url": "https://ya.ru/",
+                    "verified": true
+                }
+            }"#,
+        )
+        .unwrap();
+
+        assert!(!host.is_https());
+        assert!(host.main_mirror.unwrap().is_https());
+    }
+
+    #[test]
+    fn test_query_analytics_to_long() {
+        let response: QueryAnalyticsResponse = serde_json::from_str(
+            r#"{
+                "indicators": {
+                    "TOTAL_CLICKS": [
+                        { "date": "2024-01-02T00:00:00.000+0300", "value": 12.0 },
+                        { "date": "2024-01-01T00:00:00.000+0300", "value": 10.0 }
+                    ],
+                    "TOTAL_SHOWS": [
+                        { "date": "2024-01-01T00:00:00.000+0300", "value": 100.0 },
+                        { "date": "2024-01-02T00:00:00.000+0300", "value": 110.0 },
+                        { "date": "2024-01-03T00:00:00.000+0300", "value": 120.0 }
+                    ]
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let rows = response.to_long();
+        let points: usize = response.indicators.values().map(Vec::len).sum();
+        assert_eq!(rows.len(), points);
+
+        let order: Vec<_> = rows
+            .iter()
+            .map(|(_, indicator, value)| (*indicator, *value))
+            .collect();
+        assert_eq!(
+            order,
+            vec![
+                (ApiQueryIndicator::TotalShows, 100.0),
+                (ApiQueryIndicator::TotalClicks, 10.0),
+                (ApiQueryIndicator::TotalShows, 110.0),
+                (ApiQueryIndicator::TotalClicks, 12.0),
+                (ApiQueryIndicator::TotalShows, 120.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sitemaps_filter_by_source() {
+        let robots = SitemapInfo {
+            sitemap_id: "robots".to_string(),
+            sources: vec![ApiSitemapSource::RobotsTxt, ApiSitemapSource::Webmaster],
+            ..SitemapInfo::sample()
+        };
+        let response = SitemapsResponse {
+            sitemaps: vec![SitemapInfo::sample(), robots.clone()],
+        };
+
+        assert_eq!(
+            response.filter_by_source(ApiSitemapSource::RobotsTxt),
+            vec![&robots]
+        );
+        assert_eq!(
+            response.filter_by_source(ApiSitemapSource::Webmaster).len(),
+            2
+        );
+        assert!(response
+            .filter_by_source(ApiSitemapSource::IndexSitemap)
+            .is_empty());
+
+        let summary = response.sources_summary();
+        assert_eq!(summary[&ApiSitemapSource::Webmaster], 2);
+        assert_eq!(summary[&ApiSitemapSource::RobotsTxt], 1);
+        assert!(!summary.contains_key(&ApiSitemapSource::IndexSitemap));
+    }
+
+    #[test]
+    fn test_parsed_host_id() {
+        let parsed = ParsedHostId::parse("http:ya.ru:80").unwrap();
+        assert_eq!(
+            parsed,
+            ParsedHostId {
+                scheme: "http".to_string(),
+                host: "ya.ru".to_string(),
+                port: 80,
+            }
+        );
+        assert!(parsed.is_default_port());
+        assert_eq!(parsed.to_string(), "http:ya.ru:80");
+        assert_eq!(parsed.url(), "http://ya.ru/");
+
+        let parsed: ParsedHostId = "https:ya.ru".parse().unwrap();
+        assert_eq!(parsed.to_string(), "https:ya.ru:443");
+
+        let parsed = ParsedHostId::parse("https:example.com:8443").unwrap();
+        assert!(!parsed.is_default_port());
+        assert_eq!(parsed.url(), "https://example.com:8443/");
+
+        let parsed = ParsedHostId::parse("https:пример.рф:443").unwrap();
+        assert_eq!(parsed.host, "xn--e1afmkfd.xn--p1ai");
+        assert_eq!(
+            ParsedHostId::from_url("https://пример.рф/").unwrap(),
+            parsed
+        );
+
+        for invalid in ["ya.ru", "ftp:ya.ru:21", "http::80", "http:ya.ru:port"] {
+            assert!(
+                matches!(
+                    ParsedHostId::parse(invalid),
+                    Err(YandexWebmasterError::InvalidHostId(_))
+                ),
+                "{invalid}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_query_string() {
+        let request = QueryHistoryRequest::builder()
+            .query_indicator(vec![
+                ApiQueryIndicator::TotalShows,
+                ApiQueryIndicator::TotalClicks,
+            ])
+            .date_from(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap())
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            request.to_query_string().unwrap(),
+            "query_indicator=TOTAL_SHOWS&query_indicator=TOTAL_CLICKS&date_from=2024-01-01"
+        );
+    }
+
+    #[test]
+    fn test_indexing_status_buckets() {
+        assert_eq!(
+            IndexingStatusEnum::from_http_code(204),
+            IndexingStatusEnum::Http2xx
+        );
+        assert_eq!(
+            IndexingStatusEnum::from_http_code(503),
+            IndexingStatusEnum::Http5xx
+        );
+        assert_eq!(
+            IndexingStatusEnum::from_http_code(1003),
+            IndexingStatusEnum::Other
+        );
+
+        let status = |status, http_code| IndexingStatus {
+            status,
+            http_code,
+            access_date: Utc::now(),
+        };
+        assert!(status(IndexingStatusEnum::Http2xx, None).is_ok());
+        assert!(status(IndexingStatusEnum::Http4xx, Some(404)).is_client_error());
+        assert!(status(IndexingStatusEnum::Http5xx, None).is_server_error());
+        assert!(!status(IndexingStatusEnum::Other, Some(1003)).is_ok());
+        assert!(status(IndexingStatusEnum::Other, Some(500)).is_server_error());
+    }
+
+    #[test]
+    fn test_query_indicator_validation() {
+        let request = QueryAnalyticsRequest::builder()
+            .query_indicator(vec![])
+            .build();
+        assert!(matches!(
+            request,
+            Err(YandexWebmasterError::InvalidRequest(_))
+        ));
+
+        let request = QueryAnalyticsRequest {
+            query_indicator: vec![],
+            device_type_indicator: None,
+            date_from: None,
+            date_to: None,
+        };
+        assert!(matches!(
+            request.validate(),
+            Err(YandexWebmasterError::InvalidRequest(_))
+        ));
+
+        let request = QueryHistoryRequest::builder()
+            .query_indicator(vec![ApiQueryIndicator::TotalShows])
+            .build()
+            .unwrap();
+        assert!(request.validate().is_ok());
+        assert_eq!(
+            request.to_query_string().unwrap(),
+            "query_indicator=TOTAL_SHOWS"
+        );
+    }
+
+    #[test]
+    fn test_indexing_history_merge() {
+        let history = |points: &[(u32, f64)]| IndexingHistoryResponse {
+            indicators: HashMap::from([(
+                IndexingStatusEnum::Http2xx,
+                points
+                    .iter()
+                    .map(|&(day, value)| IndexingHistoryPoint {
+                        date: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+                        value,
+                    })
+                    .collect(),
+            )]),
+        };
+
+        let merged = IndexingHistoryResponse::merge(vec![
+            history(&[(3, 30.0), (1, 10.0), (2, 20.0)]),
+            history(&[(4, 40.0), (3, 31.0)]),
+        ]);
+
+        let values: Vec<f64> = merged.indicators[&IndexingStatusEnum::Http2xx]
+            .iter()
+            .map(|p| p.value)
+            .collect();
+        assert_eq!(values, vec![10.0, 20.0, 31.0, 40.0]);
+    }
+
+    #[test]
+    fn test_host_display_name() {
+        let mut host = FullHostInfo {
+            ascii_host_url: "https://xn--e1afmkfd.xn--p1ai/".to_string(),
+            unicode_host_url: "https://пример.рф/".to_string(),
+            host_display_name: Some("Пример".to_string()),
+            ..FullHostInfo::sample()
+        };
+        assert_eq!(host.display_name(), "Пример");
+
+        host.host_display_name = None;
+        assert_eq!(host.display_name(), "https://пример.рф/");
+
+        host.unicode_host_url.clear();
+        assert_eq!(host.display_name(), "https://xn--e1afmkfd.xn--p1ai/");
+
+        assert_eq!(HostInfo::sample().display_name(), "https://example.com/");
+    }
+
+    #[test]
+    fn test_reason_summary() {
+        let date = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut sample = SearchEventsSample {
+            url: "https://example.com/page".to_string(),
+            title: "Page".to_string(),
+            event_date: date,
+            last_access: date,
+            event: ApiSearchEventEnum::RemovedFromSearch,
+            excluded_url_status: Some(ApiExcludedUrlStatus::HttpError),
+            bad_http_status: Some(404),
+            target_url: None,
+        };
+        assert_eq!(
+            sample.reason_summary(),
+            "The page was removed from search: the page responded with HTTP 404."
+        );
+
+        sample.event = ApiSearchEventEnum::AppearedInSearch;
+        assert_eq!(sample.reason_summary(), "The page appeared in search.");
+
+        let mut status = SearchStatus {
+            title: "Page".to_string(),
+            description: None,
+            last_access: date,
+            excluded_url_status: Some(ApiExcludedUrlStatus::Duplicate),
+            bad_http_status: None,
+            searchable: false,
+            target_url: Some("https://example.com/".to_string()),
+        };
+        assert_eq!(
+            status.reason_summary(),
+            "The page is excluded from search: the page duplicates a page already in search \
+             (target: https://example.com/)."
+        );
+
+        status.excluded_url_status = None;
+        status.target_url = None;
+        assert_eq!(status.reason_summary(), "The page is excluded from search.");
+
+        status.searchable = true;
+        assert_eq!(status.reason_summary(), "The page is in search.");
+    }
+
+    #[test]
+    fn test_popular_queries_date_range() {
+        let response = PopularQueriesResponse {
+            queries: Vec::new(),
+            date_from: NaiveDate::from_ymd_opt(2024, 1, 30).unwrap(),
+            date_to: NaiveDate::from_ymd_opt(2024, 2, 2).unwrap(),
+            count: 0,
+        };
+
+        let range = response.date_range();
+        assert!(range.contains(&NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()));
+        assert!(range.contains(&NaiveDate::from_ymd_opt(2024, 2, 2).unwrap()));
+        assert!(!range.contains(&NaiveDate::from_ymd_opt(2024, 2, 3).unwrap()));
+        assert_eq!(response.days_covered(), 4);
+    }
+
+    #[test]
+    fn test_verification_fail_reason() {
+        assert!(VerificationFailReason::DnsRecordNotFound.is_retriable());
+        assert!(VerificationFailReason::WrongHtmlPageContent.is_retriable());
+        assert!(!VerificationFailReason::PddVerificationCancelled.is_retriable());
+        assert!(!VerificationFailReason::DelegationCancelled.is_retriable());
+
+        assert!(VerificationFailReason::MetaTagNotFound
+            .user_action()
+            .contains("meta tag"));
+    }
+
+    #[test]
+    fn test_indexing_history_pivot() {
+        let point = |day: u32, value: f64| IndexingHistoryPoint {
+            date: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+            value,
+        };
+        let response = IndexingHistoryResponse {
+            indicators: HashMap::from([
+                (
+                    IndexingStatusEnum::Http2xx,
+                    vec![point(1, 100.0), point(2, 110.0)],
+                ),
+                (
+                    IndexingStatusEnum::Http4xx,
+                    vec![point(2, 5.0), point(3, 7.0)],
+                ),
+            ]),
+        };
+
+        let rows = response.pivot();
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows[0].date, point(1, 0.0).date);
+        assert_eq!((rows[0].http_2xx, rows[0].http_4xx), (100.0, 0.0));
+        assert_eq!((rows[1].http_2xx, rows[1].http_4xx), (110.0, 5.0));
+        assert_eq!((rows[2].http_2xx, rows[2].http_4xx), (0.0, 7.0));
+        assert_eq!(rows[2].other, 0.0);
+    }
+
+    #[test]
+    fn test_popular_queries_ctr() {
+        let query = |indicators: &[(ApiQueryIndicator, f64)]| PopularQuery {
+            query_id: "1".to_string(),
+            query_text: "query".to_string(),
+            indicators: indicators.iter().copied().collect(),
+        };
+        let queries = vec![
+            query(&[
+                (ApiQueryIndicator::TotalShows, 100.0),
+                (ApiQueryIndicator::TotalClicks, 10.0),
+            ]),
+            query(&[
+                (ApiQueryIndicator::TotalShows, 0.0),
+                (ApiQueryIndicator::TotalClicks, 0.0),
+            ]),
+            query(&[(ApiQueryIndicator::TotalShows, 300.0)]),
+            query(&[
+                (ApiQueryIndicator::TotalShows, 100.0),
+                (ApiQueryIndicator::TotalClicks, 30.0),
+            ]),
+        ];
+
+        assert_eq!(queries[0].ctr(), Some(0.1));
+        assert_eq!(queries[1].ctr(), None);
+        assert_eq!(queries[2].ctr(), None);
+
+        let mut response = PopularQueriesResponse {
+            queries,
+            date_from: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            date_to: NaiveDate::from_ymd_opt(2024, 1, 31).unwrap(),
+            count: 4,
+        };
+        assert_eq!(response.average_ctr(), Some(0.2));
+
+        response.queries.truncate(3);
+        response.queries.remove(0);
+        assert_eq!(response.average_ctr(), None);
+    }
+
+    #[test]
+    fn test_verification_token() {
+        let token: VerificationToken = serde_json::from_str(r#""d4f3a9b1c2e5f678""#).unwrap();
+
+        assert_eq!(token.as_dns_txt(), "yandex-verification: d4f3a9b1c2e5f678");
+        assert_eq!(
+            token.as_meta_tag(),
+            r#"<meta name="yandex-verification" content="d4f3a9b1c2e5f678" />"#
+        );
+        assert_eq!(token.as_html_filename(), "yandex_d4f3a9b1c2e5f678.html");
+        assert!(token
+            .as_html_file_content()
+            .contains("<body>Verification: d4f3a9b1c2e5f678</body>"));
+        assert_eq!(
+            serde_json::to_string(&token).unwrap(),
+            r#""d4f3a9b1c2e5f678""#
+        );
+    }
+
+    #[test]
+    fn test_host_summary_delta() {
+        let previous = HostSummaryResponse {
+            sqi: None,
+            searchable_pages_count: 100,
+            excluded_pages_count: 10,
+            site_problems: HashMap::from([
+                (SiteProblemSeverityEnum::Critical, 2),
+                (SiteProblemSeverityEnum::Recommendation, 1),
+            ]),
+        };
+        let current = HostSummaryResponse {
+            sqi: Some(20.0),
+            searchable_pages_count: 120,
+            excluded_pages_count: 5,
+            site_problems: HashMap::from([
+                (SiteProblemSeverityEnum::Fatal, 1),
+                (SiteProblemSeverityEnum::Recommendation, 1),
+            ]),
+        };
+
+        let delta = current.delta(&previous);
+        assert_eq!(delta.sqi_change(), None);
+        assert_eq!(delta.sqi_current, Some(20.0));
+        assert_eq!(delta.searchable_pages_count, 20);
+        assert_eq!(delta.excluded_pages_count, -5);
+        assert_eq!(
+            delta.site_problems,
+            HashMap::from([
+                (SiteProblemSeverityEnum::Fatal, 1),
+                (SiteProblemSeverityEnum::Critical, -2),
+            ])
+        );
+        assert!(!delta.is_empty());
+
+        let next = HostSummaryResponse {
+            sqi: Some(30.0),
+            ..current.clone()
+        };
+        assert_eq!(next.delta(&current).sqi_change(), Some(10.0));
+        assert!(current.delta(&current).is_empty());
+    }
+
+    #[test]
+    fn test_host_verification_instructions() {
+        let response = HostVerificationResponse {
+            verification_state: VerificationState::None,
+            verification_type: VerificationType::Auto,
+            verification_uin: VerificationToken("d4f3a9b1c2e5f678".to_string()),
+            applicable_verifiers: vec![
+                ExplicitVerificationType::Dns,
+                ExplicitVerificationType::HtmlFile,
+            ],
+        };
+
+        let instructions = response.instructions();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[0].method, ExplicitVerificationType::Dns);
+        assert_eq!(
+            instructions[0].detail,
+            VerificationDetail::Dns {
+                txt_value: "yandex-verification: d4f3a9b1c2e5f678".to_string()
+            }
+        );
+        match &instructions[1].detail {
+            VerificationDetail::HtmlFile { file_name, content } => {
+                assert_eq!(file_name, "yandex_d4f3a9b1c2e5f678.html");
+                assert_eq!(content, &response.verification_uin.as_html_file_content());
+            }
+            other => panic!("unexpected detail: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_request_builders() {
+        // Every request type is built the same way, with required fields set first
+        assert_eq!(
+            SqiHistoryRequest::builder()
+                .build()
+                .to_query_string()
+                .unwrap(),
+            ""
+        );
+        assert_eq!(
+            PopularQueriesRequest::builder()
+                .order_by(ApiQueryOrderField::TotalShows)
+                .limit(10)
+                .build()
+                .to_query_string()
+                .unwrap(),
+            "order_by=TOTAL_SHOWS&limit=10"
+        );
+        assert_eq!(
+            GetSitemapsRequest::builder()
+                .limit(10)
+                .build()
+                .to_query_string()
+                .unwrap(),
+            "limit=10"
+        );
+        assert_eq!(
+            GetUserSitemapsRequest::builder()
+                .offset(20)
+                .build()
+                .to_query_string()
+                .unwrap(),
+            "offset=20"
+        );
+        assert_eq!(
+            IndexingHistoryRequest::builder()
+                .build()
+                .to_query_string()
+                .unwrap(),
+            ""
+        );
+        assert_eq!(
+            GetIndexingSamplesRequest::builder()
+                .offset(20)
+                .limit(10)
+                .build()
+                .to_query_string()
+                .unwrap(),
+            "offset=20&limit=10"
+        );
+        assert_eq!(
+            GetSearchUrlsSamplesRequest::builder()
+                .limit(10)
+                .build()
+                .to_query_string()
+                .unwrap(),
+            "limit=10"
+        );
+        assert_eq!(
+            GetSearchEventsSamplesRequest::builder()
+                .event(ApiSearchEventEnum::AppearedInSearch)
+                .build()
+                .to_query_string()
+                .unwrap(),
+            "event=APPEARED_IN_SEARCH"
+        );
+        assert_eq!(
+            GetRecrawlTasksRequest::builder()
+                .limit(10)
+                .build()
+                .to_query_string()
+                .unwrap(),
+            "limit=10"
+        );
+        assert_eq!(
+            BrokenLinksRequest::builder()
+                .indicator(vec![ApiInternalLinksBrokenIndicator::SiteError])
+                .build()
+                .to_query_string()
+                .unwrap(),
+            "indicator=SITE_ERROR"
+        );
+        assert_eq!(
+            BrokenLinkHistoryRequest::builder()
+                .build()
+                .to_query_string()
+                .unwrap(),
+            ""
+        );
+        assert_eq!(
+            ExternalLinksRequest::builder()
+                .offset(20)
+                .build()
+                .to_query_string()
+                .unwrap(),
+            "offset=20"
+        );
+        assert_eq!(
+            serde_json::to_value(
+                AddFeedRequest::builder()
+                    .url("https://example.com/feed.xml")
+                    .feed_type(FeedType::Goods)
+                    .region_ids([213])
+                    .build()
+            )
+            .unwrap(),
+            serde_json::json!({ "url": "https://example.com/feed.xml", "type": "GOODS", "regionIds": [213] })
+        );
+
+        // Builders of requests with validation return a `Result`
+        let request = QueryAnalyticsRequest::builder()
+            .query_indicator([ApiQueryIndicator::TotalShows])
+            .build()
+            .unwrap();
+        assert_eq!(
+            request.to_query_string().unwrap(),
+            "query_indicator=TOTAL_SHOWS"
+        );
+        assert!(matches!(
+            QueryHistoryRequest::builder()
+                .query_indicator(Vec::new())
+                .build(),
+            Err(YandexWebmasterError::InvalidRequest(_))
+        ));
+    }
+
+    #[test]
+    fn test_query_history_rows() {
+        let point = |day, value| IndicatorPoint {
+            date: Utc.with_ymd_and_hms(2024, 1, day, 0, 0, 0).unwrap(),
+            value,
+        };
+        let response = QueryHistoryResponse {
+            query_id: "c3f9a0b5".to_string(),
+            query_text: "yandex webmaster".to_string(),
+            indicators: HashMap::from([
+                (
+                    ApiQueryIndicator::TotalClicks,
+                    vec![point(2, 3.0), point(1, 1.0)],
+                ),
+                (ApiQueryIndicator::TotalShows, vec![point(1, 10.0)]),
+            ]),
+        };
+
+        let rows = response.rows();
+        let values: Vec<_> = rows.iter().map(|r| (r.indicator, r.value)).collect();
+        assert_eq!(
+            values,
+            [
+                (ApiQueryIndicator::TotalShows, 10.0),
+                (ApiQueryIndicator::TotalClicks, 1.0),
+                (ApiQueryIndicator::TotalClicks, 3.0)
+            ]
+        );
+        assert!(rows.iter().all(|r| r.query_text == "yandex webmaster"));
+    }
+}
@@ -98,6 +98,25 @@ impl fmt::Display for YandexErrorCode {
     }
 }
 
+impl From<reqwest::StatusCode> for YandexErrorCode {
+    /// Best-effort error code for responses without a structured error body
+    ///
+    /// Statuses without a matching code map to `Unknown("HTTP_<status>")`.
+    fn from(status: reqwest::StatusCode) -> Self {
+        match status.as_u16() {
+            401 => YandexErrorCode::InvalidOauthToken,
+            403 => YandexErrorCode::AccessForbidden,
+            404 => YandexErrorCode::ResourceNotFound,
+            405 => YandexErrorCode::MethodNotAllowed,
+            406 => YandexErrorCode::ContentTypeUnsupported,
+            413 => YandexErrorCode::RequestEntityTooLarge,
+            415 => YandexErrorCode::ContentEncodingUnsupported,
+            429 => YandexErrorCode::TooManyRequestsError,
+            status => YandexErrorCode::Unknown(format!("HTTP_{}", status)),
+        }
+    }
+}
+
 /// Response structure for Yandex API errors
 ///
 /// This struct represents the error response format returned by the Yandex Webmaster API.
@@ -117,10 +136,25 @@ pub struct YandexApiErrorResponse {
     /// Optional expiration date (for 410 errors)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub valid_until: Option<String>,
+
+    /// Optional host ID the error refers to (e.g. for `HOST_ALREADY_ADDED`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_id: Option<String>,
+
+    /// Optional sitemap ID the error refers to (e.g. for `SITEMAP_ALREADY_ADDED`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sitemap_id: Option<String>,
+
+    /// Optional limit that was exceeded (e.g. for `HOSTS_LIMIT_EXCEEDED`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<i64>,
 }
 
 /// Errors that can occur when interacting with the Yandex Webmaster API
+///
+/// New variants may be added in minor releases, so matches need a wildcard arm.
 #[derive(Debug, Error)]
+#[non_exhaustive]
 pub enum YandexWebmasterError {
     /// HTTP request failed
     #[error("HTTP request failed: {0}")]
@@ -134,6 +168,28 @@ pub enum YandexWebmasterError {
     #[error("Failed to parse response: {0}")]
     ParseError(#[from] serde_json::Error),
 
+    /// Failed to parse the response of an endpoint
+    #[error("Failed to parse response of {url} at `{path}`: {source}")]
+    ResponseParseError {
+        /// URL of the request
+        url: String,
+        /// Path of the field that failed to parse (e.g. `sitemaps[0].errors_count`)
+        path: String,
+        /// Underlying error
+        source: serde_json::Error,
+    },
+
+    /// Response body ended before a complete JSON document (e.g. the connection dropped)
+    #[error("Truncated response of {url} after {received} bytes: {source}")]
+    TruncatedResponse {
+        /// URL of the request
+        url: String,
+        /// Number of bytes received
+        received: usize,
+        /// Underlying error
+        source: serde_json::Error,
+    },
+
     /// Failed to serialize url
     #[error("Failed serialize url: {0}")]
     SerdeQsError(#[from] serde_qs::Error),
@@ -152,17 +208,325 @@ pub enum YandexWebmasterError {
         /// HTTP status code
         status: u16,
         /// Yandex API error response
-        response: YandexApiErrorResponse,
+        response: Box<YandexApiErrorResponse>,
+        /// ID sent in the `X-Request-Id` header, if request IDs are enabled
+        request_id: Option<String>,
+    },
+
+    /// API rejected the content type of the request body (`CONTENT_TYPE_UNSUPPORTED`)
+    #[error("Content type unsupported, acceptable types: {}", acceptable_types.join(", "))]
+    ContentTypeUnsupported {
+        /// HTTP status code
+        status: u16,
+        /// Content types accepted by the endpoint
+        acceptable_types: Vec<String>,
+        /// Yandex API error response
+        response: Box<YandexApiErrorResponse>,
+        /// ID sent in the `X-Request-Id` header, if request IDs are enabled
+        request_id: Option<String>,
+    },
+
+    /// Endpoint doesn't accept the HTTP method of the request (405)
+    #[error("Method not allowed, allowed methods: {}", allow.join(", "))]
+    MethodNotAllowed {
+        /// Methods listed in the `Allow` header of the response, empty if it was missing
+        allow: Vec<String>,
+        /// Error code of the structured body, `METHOD_NOT_ALLOWED` if there was none
+        error_code: YandexErrorCode,
+        /// Response body
+        body: String,
+        /// ID sent in the `X-Request-Id` header, if request IDs are enabled
+        request_id: Option<String>,
+    },
+
+    /// API returned an error without a structured body (e.g. an HTML page of a gateway)
+    #[error("API error ({error_code}), status: {status}, body: {body}")]
+    UnstructuredApiError {
+        /// HTTP status code
+        status: u16,
+        /// Error code guessed from the HTTP status
+        error_code: YandexErrorCode,
+        /// Raw response body
+        body: String,
+        /// ID sent in the `X-Request-Id` header, if request IDs are enabled
+        request_id: Option<String>,
     },
 
-    /// API returned an unstructured error
+    /// The body of an error response couldn't be read
+    ///
+    /// Error bodies that are read but aren't JSON are returned as [`Self::UnstructuredApiError`].
     #[error("API error: {0}")]
     GenericApiError(String),
+
+    /// The client is rate limited, the request wasn't sent
+    #[error("Rate limit exhausted, retry after {retry_after:?}")]
+    RateLimitExhausted {
+        /// Time left until the limit resets
+        retry_after: std::time::Duration,
+    },
+
+    /// The request body exceeds the configured size limit, the request wasn't sent
+    #[error("Request body of {size} bytes exceeds the limit of {limit} bytes")]
+    PayloadTooLarge {
+        /// Size of the serialized body
+        size: usize,
+        /// Configured maximum size
+        limit: usize,
+    },
+
+    /// The user has no verified sites
+    #[error("No verified hosts found")]
+    NoVerifiedHosts,
+
+    /// None of the explicit verification methods can be used for the site
+    #[error("No applicable verification methods for host {0}")]
+    NoApplicableVerifiers(String),
+
+    /// The API returned no indexing status for the URL
+    #[error("No indexing status available for URL {0}")]
+    IndexingStatusUnavailable(String),
+
+    /// Request parameters are rejected before sending the request
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// Host ID doesn't match the `scheme:host:port` format
+    #[error("Invalid host ID: {0}")]
+    InvalidHostId(String),
+
+    /// Sitemap URL doesn't belong to the host it is added to
+    #[error("Sitemap URL {sitemap_url} doesn't belong to host {host_url}")]
+    SitemapHostMismatch {
+        /// Sitemap URL
+        sitemap_url: String,
+        /// URL of the host
+        host_url: String,
+    },
+}
+
+impl YandexWebmasterError {
+    /// Returns the Yandex API error code if the API returned a structured error
+    ///
+    /// For errors without a structured body the code is guessed from the HTTP status.
+    pub fn error_code(&self) -> Option<&YandexErrorCode> {
+        match self {
+            YandexWebmasterError::ApiError { response, .. }
+            | YandexWebmasterError::ContentTypeUnsupported { response, .. } => {
+                Some(&response.error_code)
+            }
+            YandexWebmasterError::UnstructuredApiError { error_code, .. }
+            | YandexWebmasterError::MethodNotAllowed { error_code, .. } => Some(error_code),
+            _ => None,
+        }
+    }
+
+    /// Returns the ID of the failed request if request IDs are enabled
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            YandexWebmasterError::ApiError { request_id, .. }
+            | YandexWebmasterError::ContentTypeUnsupported { request_id, .. }
+            | YandexWebmasterError::MethodNotAllowed { request_id, .. }
+            | YandexWebmasterError::UnstructuredApiError { request_id, .. } => {
+                request_id.as_deref()
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the exceeded limit for the limit-related error codes
+    ///
+    /// Available for `HOSTS_LIMIT_EXCEEDED`, `FEEDS_LIMIT_EXCEEDED`, `BATCH_LIMIT_EXCEEDED`
+    /// and `LIMITS_EXCEEDED` if the API reported the limit.
+    pub fn limit(&self) -> Option<i64> {
+        match self {
+            YandexWebmasterError::ApiError { response, .. } => match response.error_code {
+                YandexErrorCode::HostsLimitExceeded
+                | YandexErrorCode::FeedsLimitExceeded
+                | YandexErrorCode::BatchLimitExceeded
+                | YandexErrorCode::LimitsExceeded => response.limit,
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Returns the category of the error
+    pub fn kind(&self) -> ErrorKind {
+        if self.is_rate_limited() {
+            return ErrorKind::RateLimited;
+        }
+        if self.is_not_found() {
+            return ErrorKind::NotFound;
+        }
+
+        match self {
+            YandexWebmasterError::HttpError(e) if e.is_decode() => ErrorKind::Parse,
+            YandexWebmasterError::HttpError(_)
+            | YandexWebmasterError::MiddlewareHttpError(_)
+            | YandexWebmasterError::TruncatedResponse { .. } => ErrorKind::Network,
+            YandexWebmasterError::ParseError(_)
+            | YandexWebmasterError::ResponseParseError { .. } => ErrorKind::Parse,
+            YandexWebmasterError::AuthenticationError => ErrorKind::Forbidden,
+            YandexWebmasterError::SerdeQsError(_)
+            | YandexWebmasterError::ContentTypeUnsupported { .. }
+            | YandexWebmasterError::MethodNotAllowed { .. }
+            | YandexWebmasterError::InvalidRequest(_)
+            | YandexWebmasterError::PayloadTooLarge { .. }
+            | YandexWebmasterError::InvalidHostId(_)
+            | YandexWebmasterError::SitemapHostMismatch { .. } => ErrorKind::Validation,
+            YandexWebmasterError::ApiError { status, .. }
+            | YandexWebmasterError::UnstructuredApiError { status, .. } => match status {
+                401 | 403 => ErrorKind::Forbidden,
+                404 | 410 => ErrorKind::NotFound,
+                400 | 405 | 406 | 409 | 413 | 415 | 422 => ErrorKind::Validation,
+                _ => ErrorKind::Other,
+            },
+            _ => ErrorKind::Other,
+        }
+    }
+
+    /// Returns `true` if the request was rejected because of the rate limit
+    ///
+    /// Matches the `QUOTA_EXCEEDED` and `TOO_MANY_REQUESTS_ERROR` error codes and requests
+    /// skipped because of [`YandexWebmasterError::RateLimitExhausted`].
+    pub fn is_rate_limited(&self) -> bool {
+        matches!(self, YandexWebmasterError::RateLimitExhausted { .. })
+            || matches!(
+                self.error_code(),
+                Some(YandexErrorCode::QuotaExceeded | YandexErrorCode::TooManyRequestsError)
+            )
+    }
+
+    /// Returns `true` if repeating the request may succeed
+    ///
+    /// Matches truncated responses, connection failures and timeouts, rate limits and
+    /// server errors (5xx).
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            YandexWebmasterError::TruncatedResponse { .. }
+            | YandexWebmasterError::RateLimitExhausted { .. } => true,
+            YandexWebmasterError::HttpError(e) => e.is_connect() || e.is_timeout() || e.is_body(),
+            YandexWebmasterError::MiddlewareHttpError(e) => e.is_connect() || e.is_timeout(),
+            YandexWebmasterError::ApiError { status, .. }
+            | YandexWebmasterError::UnstructuredApiError { status, .. } => {
+                *status >= 500 || self.is_rate_limited()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the requested resource doesn't exist
+    ///
+    /// Matches the `HOST_NOT_FOUND`, `SITEMAP_NOT_FOUND`, `TASK_NOT_FOUND` and
+    /// `RESOURCE_NOT_FOUND` error codes.
+    pub fn is_not_found(&self) -> bool {
+        matches!(
+            self.error_code(),
+            Some(
+                YandexErrorCode::HostNotFound
+                    | YandexErrorCode::SitemapNotFound
+                    | YandexErrorCode::TaskNotFound
+                    | YandexErrorCode::ResourceNotFound
+            )
+        )
+    }
+
+    /// Returns `true` if the resource being added already exists
+    ///
+    /// Matches the `HOST_ALREADY_ADDED`, `SITEMAP_ALREADY_ADDED`, `FEED_ALREADY_ADDED`
+    /// and `URL_ALREADY_ADDED` error codes.
+    pub fn is_already_added(&self) -> bool {
+        matches!(
+            self.error_code(),
+            Some(
+                YandexErrorCode::HostAlreadyAdded
+                    | YandexErrorCode::SitemapAlreadyAdded
+                    | YandexErrorCode::FeedAlreadyAdded
+                    | YandexErrorCode::UrlAlreadyAdded
+            )
+        )
+    }
+}
+
+/// Category of a [`YandexWebmasterError`] for mapping errors to other APIs
+///
+/// New categories may be added, so matches need a wildcard arm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The request was rejected by the rate limit or quota
+    RateLimited,
+    /// The requested resource doesn't exist
+    NotFound,
+    /// The token is invalid or lacks access to the resource
+    Forbidden,
+    /// The request parameters are invalid
+    Validation,
+    /// The request or response failed in transit
+    Network,
+    /// The response couldn't be parsed
+    Parse,
+    /// Any other error
+    Other,
+}
+
+impl From<&YandexWebmasterError> for ErrorKind {
+    fn from(error: &YandexWebmasterError) -> Self {
+        error.kind()
+    }
 }
 
 /// Result type alias for Yandex Webmaster API operations
 pub type Result<T> = std::result::Result<T, YandexWebmasterError>;
 
+/// Results of a multi-item operation where some items may fail
+///
+/// Failed items are kept with their keys (e.g. host IDs) instead of failing the whole call.
+#[derive(Debug)]
+pub struct PartialResult<T> {
+    /// Successful results
+    pub ok: Vec<T>,
+    /// Keys of failed items with their errors
+    pub errors: Vec<(String, YandexWebmasterError)>,
+}
+
+impl<T> Default for PartialResult<T> {
+    fn default() -> Self {
+        Self {
+            ok: Vec::new(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl<T> PartialResult<T> {
+    /// Returns `true` if no item failed
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Returns the successful results, or the first error if every item failed
+    pub fn into_result(self) -> Result<Vec<T>> {
+        match (self.ok.is_empty(), self.errors.into_iter().next()) {
+            (true, Some((_, error))) => Err(error),
+            _ => Ok(self.ok),
+        }
+    }
+}
+
+impl<T> FromIterator<(String, Result<T>)> for PartialResult<T> {
+    fn from_iter<I: IntoIterator<Item = (String, Result<T>)>>(iter: I) -> Self {
+        let mut result = Self::default();
+        for (key, item) in iter {
+            match item {
+                Ok(value) => result.ok.push(value),
+                Err(error) => result.errors.push((key, error)),
+            }
+        }
+        result
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -252,6 +616,14 @@ mod tests {
         let result: YandexApiErrorResponse = serde_json::from_str(json).unwrap();
         assert_eq!(result.error_code, YandexErrorCode::HostsLimitExceeded);
         assert_eq!(result.error_message, "explicit error message");
+        assert_eq!(result.limit, Some(1));
+
+        let error = YandexWebmasterError::ApiError {
+            status: 403,
+            response: Box::new(result),
+            request_id: None,
+        };
+        assert_eq!(error.limit(), Some(1));
     }
 
     #[test]
@@ -402,6 +774,7 @@ mod tests {
         let result: YandexApiErrorResponse = serde_json::from_str(json).unwrap();
         assert_eq!(result.error_code, YandexErrorCode::HostAlreadyAdded);
         assert_eq!(result.error_message, "some string");
+        assert_eq!(result.host_id, Some("http:ya.ru:80".to_string()));
     }
 
     #[test]
@@ -431,6 +804,7 @@ mod tests {
         let result: YandexApiErrorResponse = serde_json::from_str(json).unwrap();
         assert_eq!(result.error_code, YandexErrorCode::SitemapAlreadyAdded);
         assert_eq!(result.error_message, "some string");
+        assert_eq!(result.sitemap_id, Some("c7-fe:80-c0".to_string()));
     }
 
     #[test]
@@ -530,12 +904,16 @@ mod tests {
     fn test_error_display() {
         let error = YandexWebmasterError::ApiError {
             status: 404,
-            response: YandexApiErrorResponse {
+            response: Box::new(YandexApiErrorResponse {
                 error_code: YandexErrorCode::HostNotFound,
                 error_message: "Host not found in user's list".to_string(),
                 acceptable_types: None,
                 valid_until: None,
-            },
+                host_id: None,
+                sitemap_id: None,
+                limit: None,
+            }),
+            request_id: None,
         };
 
         let error_string = error.to_string();
@@ -543,6 +921,31 @@ mod tests {
         assert!(error_string.contains("Host not found in user's list"));
     }
 
+    #[test]
+    fn test_error_is_not_found() {
+        let error = |error_code| YandexWebmasterError::ApiError {
+            status: 404,
+            response: Box::new(YandexApiErrorResponse {
+                error_code,
+                error_message: "some string".to_string(),
+                acceptable_types: None,
+                valid_until: None,
+                host_id: None,
+                sitemap_id: None,
+                limit: None,
+            }),
+            request_id: None,
+        };
+
+        assert!(error(YandexErrorCode::HostNotFound).is_not_found());
+        assert!(error(YandexErrorCode::TaskNotFound).is_not_found());
+        assert!(!error(YandexErrorCode::HostNotIndexed).is_not_found());
+        assert!(!YandexWebmasterError::GenericApiError("Not Found".to_string()).is_not_found());
+        assert!(error(YandexErrorCode::FeedAlreadyAdded).is_already_added());
+        assert!(!error(YandexErrorCode::FeedsLimitExceeded).is_already_added());
+        assert_eq!(error(YandexErrorCode::FeedsLimitExceeded).limit(), None);
+    }
+
     #[test]
     fn test_parse_with_extra_fields_ignored() {
         // Test that extra fields in the JSON are ignored
@@ -557,4 +960,75 @@ mod tests {
         assert_eq!(result.error_code, YandexErrorCode::HostNotFound);
         assert_eq!(result.error_message, "Host not found");
     }
+
+    #[test]
+    fn test_error_code_from_status() {
+        assert_eq!(
+            YandexErrorCode::from(reqwest::StatusCode::NOT_FOUND),
+            YandexErrorCode::ResourceNotFound
+        );
+        assert_eq!(
+            YandexErrorCode::from(reqwest::StatusCode::TOO_MANY_REQUESTS),
+            YandexErrorCode::TooManyRequestsError
+        );
+        assert_eq!(
+            YandexErrorCode::from(reqwest::StatusCode::BAD_GATEWAY),
+            YandexErrorCode::Unknown("HTTP_502".to_string())
+        );
+
+        let error = YandexWebmasterError::UnstructuredApiError {
+            status: 404,
+            error_code: reqwest::StatusCode::NOT_FOUND.into(),
+            body: "Not Found".to_string(),
+            request_id: None,
+        };
+        assert!(error.is_not_found());
+        assert!(!error.is_rate_limited());
+    }
+
+    #[test]
+    fn test_error_kind() {
+        let api_error = |status, error_code| YandexWebmasterError::ApiError {
+            status,
+            response: Box::new(YandexApiErrorResponse {
+                error_code,
+                error_message: "some string".to_string(),
+                acceptable_types: None,
+                valid_until: None,
+                host_id: None,
+                sitemap_id: None,
+                limit: None,
+            }),
+            request_id: None,
+        };
+
+        assert_eq!(
+            api_error(429, YandexErrorCode::QuotaExceeded).kind(),
+            ErrorKind::RateLimited
+        );
+        assert_eq!(
+            api_error(404, YandexErrorCode::HostNotIndexed).kind(),
+            ErrorKind::NotFound
+        );
+        assert_eq!(
+            api_error(403, YandexErrorCode::InvalidOauthToken).kind(),
+            ErrorKind::Forbidden
+        );
+        assert_eq!(
+            api_error(400, YandexErrorCode::FieldValidationError).kind(),
+            ErrorKind::Validation
+        );
+        assert_eq!(
+            api_error(500, YandexErrorCode::Unknown("INTERNAL".to_string())).kind(),
+            ErrorKind::Other
+        );
+        assert_eq!(
+            ErrorKind::from(&YandexWebmasterError::InvalidHostId("ya.ru".to_string())),
+            ErrorKind::Validation
+        );
+        assert_eq!(
+            YandexWebmasterError::from(serde_json::from_str::<i32>("x").unwrap_err()).kind(),
+            ErrorKind::Parse
+        );
+    }
 }
@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+
+use crate::dto::{
+    ApiSiteProblemState, DiagnosticsResponse, HostSummaryResponse, SiteProblemSeverityEnum,
+};
+
+/// Weights used by [`compute_health_score`]
+///
+/// Every weight is the number of points subtracted from the maximum score of 100.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HealthScoreWeights {
+    /// Penalty for each present fatal problem
+    pub fatal_problem: f64,
+    /// Penalty for each present critical problem
+    pub critical_problem: f64,
+    /// Penalty for each present possible problem
+    pub possible_problem: f64,
+    /// Penalty for each present recommendation
+    pub recommendation: f64,
+    /// Maximum penalty for excluded pages, scaled by the excluded pages ratio
+    pub excluded_pages: f64,
+    /// Penalty applied when the site quality index isn't computed yet
+    pub missing_sqi: f64,
+}
+
+impl Default for HealthScoreWeights {
+    fn default() -> Self {
+        Self {
+            fatal_problem: 25.0,
+            critical_problem: 10.0,
+            possible_problem: 2.0,
+            recommendation: 0.0,
+            excluded_pages: 30.0,
+            missing_sqi: 10.0,
+        }
+    }
+}
+
+/// Site health score with its component breakdown
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HealthScore {
+    /// Final score from 0 (worst) to 100 (best)
+    pub score: f64,
+    /// Points subtracted for present diagnostics problems
+    pub problems_penalty: f64,
+    /// Points subtracted for excluded pages
+    pub excluded_pages_penalty: f64,
+    /// Points subtracted for the missing site quality index
+    pub sqi_penalty: f64,
+    /// Share of excluded pages among all known pages (0.0 - 1.0)
+    pub excluded_pages_ratio: f64,
+}
+
+/// Computes a 0-100 health score for a site
+///
+/// The score starts at 100 and is reduced by:
+/// - every problem in `diagnostics` with the [`ApiSiteProblemState::Present`] state,
+///   weighted by its severity
+/// - the ratio of excluded pages to all (searchable and excluded) pages
+/// - a missing site quality index
+///
+/// # Arguments
+///
+/// * `summary` - Site summary from `get_host_summary`
+/// * `diagnostics` - Site diagnostics from `get_diagnostics`
+///
+/// Uses [`HealthScoreWeights::default`], see [`compute_health_score_with_weights`]
+/// to customize the weights.
+pub fn compute_health_score(
+    summary: &HostSummaryResponse,
+    diagnostics: &DiagnosticsResponse,
+) -> HealthScore {
+    compute_health_score_with_weights(summary, diagnostics, &HealthScoreWeights::default())
+}
+
+/// Computes a 0-100 health score for a site using custom weights
+///
+/// See [`compute_health_score`] for the scoring rules.
+pub fn compute_health_score_with_weights(
+    summary: &HostSummaryResponse,
+    diagnostics: &DiagnosticsResponse,
+    weights: &HealthScoreWeights,
+) -> HealthScore {
+    let problems_penalty: f64 = diagnostics
+        .problems
+        .values()
+        .filter(|p| p.state == ApiSiteProblemState::Present)
+        .map(|p| match p.severity {
+            SiteProblemSeverityEnum::Fatal => weights.fatal_problem,
+            SiteProblemSeverityEnum::Critical => weights.critical_problem,
+            SiteProblemSeverityEnum::PossibleProblem => weights.possible_problem,
+            SiteProblemSeverityEnum::Recommendation => weights.recommendation,
+        })
+        .sum();
+
+    let total_pages = summary.searchable_pages_count + summary.excluded_pages_count;
+    let excluded_pages_ratio = if total_pages > 0 {
+        summary.excluded_pages_count as f64 / total_pages as f64
+    } else {
+        0.0
+    };
+    let excluded_pages_penalty = excluded_pages_ratio * weights.excluded_pages;
+
+    let sqi_penalty = if summary.sqi.is_some() {
+        0.0
+    } else {
+        weights.missing_sqi
+    };
+
+    let score = (100.0 - problems_penalty - excluded_pages_penalty - sqi_penalty).clamp(0.0, 100.0);
+
+    HealthScore {
+        score,
+        problems_penalty,
+        excluded_pages_penalty,
+        sqi_penalty,
+        excluded_pages_ratio,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn summary(json: &str) -> HostSummaryResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn diagnostics(json: &str) -> DiagnosticsResponse {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn test_health_score_perfect() {
+        let result = compute_health_score(
+            &summary(
+                r#"{ "sqi": 100.0, "searchable_pages_count": 10, "excluded_pages_count": 0 }"#,
+            ),
+            &diagnostics(r#"{ "problems": {} }"#),
+        );
+
+        assert_eq!(result.score, 100.0);
+    }
+
+    #[test]
+    fn test_health_score_breakdown() {
+        let result = compute_health_score(
+            &summary(r#"{ "searchable_pages_count": 75, "excluded_pages_count": 25 }"#),
+            &diagnostics(
+                r#"{
+                    "problems": {
+                        "DNS_ERROR": { "severity": "FATAL", "state": "PRESENT" },
+                        "NO_SITEMAPS": { "severity": "POSSIBLE_PROBLEM", "state": "PRESENT" },
+                        "SSL_CERTIFICATE_ERROR": { "severity": "CRITICAL", "state": "ABSENT" }
+                    }
+                }"#,
+            ),
+        );
+
+        assert_eq!(result.problems_penalty, 27.0);
+        assert_eq!(result.excluded_pages_ratio, 0.25);
+        assert_eq!(result.excluded_pages_penalty, 7.5);
+        assert_eq!(result.sqi_penalty, 10.0);
+        assert_eq!(result.score, 55.5);
+    }
+
+    #[test]
+    fn test_health_score_clamped() {
+        let weights = HealthScoreWeights {
+            fatal_problem: 200.0,
+            ..Default::default()
+        };
+        let result = compute_health_score_with_weights(
+            &summary(r#"{ "sqi": 10.0 }"#),
+            &diagnostics(
+                r#"{ "problems": { "DNS_ERROR": { "severity": "FATAL", "state": "PRESENT" } } }"#,
+            ),
+            &weights,
+        );
+
+        assert_eq!(result.score, 0.0);
+    }
+}
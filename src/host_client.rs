@@ -0,0 +1,152 @@
+use tracing::instrument;
+
+use crate::{client::YandexWebmasterClient, dto::*, error::Result};
+
+/// Client bound to a single host
+///
+/// Created with [`YandexWebmasterClient::host`]. Exposes host-scoped methods without
+/// the `host_id` argument. Cheap to create and clone since it shares the underlying
+/// HTTP client.
+#[derive(Debug, Clone)]
+pub struct HostClient {
+    client: YandexWebmasterClient,
+    host_id: String,
+}
+
+impl HostClient {
+    /// Creates a client bound to the host
+    pub(crate) fn new(client: YandexWebmasterClient, host_id: String) -> Self {
+        Self { client, host_id }
+    }
+
+    /// Returns the host ID
+    pub fn host_id(&self) -> &str {
+        &self.host_id
+    }
+
+    /// Returns the underlying client
+    pub fn client(&self) -> &YandexWebmasterClient {
+        &self.client
+    }
+
+    /// Get information about the site
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn info(&self) -> Result<FullHostInfo> {
+        self.client.get_host(&self.host_id).await
+    }
+
+    /// Get site statistics summary
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn summary(&self) -> Result<HostSummaryResponse> {
+        self.client.get_host_summary(&self.host_id).await
+    }
+
+    /// Get site diagnostic report
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn diagnostics(&self) -> Result<DiagnosticsResponse> {
+        self.client.get_diagnostics(&self.host_id).await
+    }
+
+    /// Get list of all sitemap files
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn sitemaps(&self, request: &GetSitemapsRequest) -> Result<SitemapsResponse> {
+        self.client.get_sitemaps(&self.host_id, request).await
+    }
+
+    /// Get list of user-submitted sitemaps
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn user_sitemaps(
+        &self,
+        request: &GetUserSitemapsRequest,
+    ) -> Result<UserSitemapsResponse> {
+        self.client.get_user_sitemaps(&self.host_id, request).await
+    }
+
+    /// Get important URLs
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn important_urls(&self) -> Result<ImportantUrlsResponse> {
+        self.client.get_important_urls(&self.host_id).await
+    }
+
+    /// Request page recrawl
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn recrawl(&self, url: &str) -> Result<RecrawlResponse> {
+        self.client.recrawl_urls(&self.host_id, url).await
+    }
+
+    /// Get recrawl quota
+    #[instrument(skip(self), fields(host_id = %self.host_id), parent = self.client.span_parent())]
+    pub async fn recrawl_quota(&self) -> Result<RecrawlQuotaResponse> {
+        self.client.get_recrawl_quota(&self.host_id).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::{MOCK_OAUTH_TOKEN, SAMPLE_HOST_ID};
+    use crate::{dto::*, YandexWebmasterClient};
+    use serde_json::json;
+    use wiremock::{
+        matchers::{body_json, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_host_client() {
+        let server = MockServer::start().await;
+        let host_path = format!("/user/42/hosts/{}", SAMPLE_HOST_ID);
+        Mock::given(method("GET"))
+            .and(path("/user"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({ "user_id": 42 })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("{host_path}/summary")))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostSummaryResponse::sample()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path(format!("{host_path}/sitemaps")))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "sitemaps": [SitemapInfo::sample()] })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path(format!("{host_path}/recrawl/queue")))
+            .and(body_json(json!({ "url": "https://example.com/page" })))
+            .respond_with(ResponseTemplate::new(202).set_body_json(json!({ "task_id": "task" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        // The user ID fetched through the first call is reused by the clone
+        let host = YandexWebmasterClient::builder(MOCK_OAUTH_TOKEN.to_string())
+            .base_url(server.uri())
+            .build_lazy()
+            .unwrap()
+            .host(SAMPLE_HOST_ID);
+        assert_eq!(host.host_id(), SAMPLE_HOST_ID);
+        assert_eq!(host.summary().await.unwrap(), HostSummaryResponse::sample());
+
+        let clone = host.clone();
+        let sitemaps = clone
+            .sitemaps(&GetSitemapsRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(sitemaps.sitemaps, [SitemapInfo::sample()]);
+        assert_eq!(
+            clone
+                .recrawl("https://example.com/page")
+                .await
+                .unwrap()
+                .task_id,
+            "task"
+        );
+        assert_eq!(clone.client().user_id(), 42);
+    }
+}
@@ -15,11 +15,36 @@
 //! }
 //! ```
 
+mod analytics;
+mod builder;
+mod cache;
+mod camel_case;
 mod client;
 mod dto;
 mod error;
+mod health;
+mod host_client;
 mod middleware;
+mod parse;
+mod report;
+mod serde_utils;
+mod snapshot;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
-pub use client::YandexWebmasterClient;
+pub use analytics::SearchAnalytics;
+pub use builder::YandexWebmasterClientBuilder;
+pub use cache::{InMemoryResponseCache, ResponseCache};
+pub use camel_case::CamelCase;
+pub use client::{ApiRegion, YandexWebmasterClient};
 pub use dto::*;
-pub use error::{Result, YandexWebmasterError};
+pub use error::{
+    ErrorKind, PartialResult, Result, YandexApiErrorResponse, YandexErrorCode, YandexWebmasterError,
+};
+pub use health::{
+    compute_health_score, compute_health_score_with_weights, HealthScore, HealthScoreWeights,
+};
+pub use host_client::HostClient;
+pub use parse::parse_response;
+pub use report::{AccountReport, AccountReportOptions, HostReport, HostReportError};
+pub use snapshot::{HostSnapshot, SnapshotOptions};
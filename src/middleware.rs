@@ -1,20 +1,118 @@
-use reqwest::{Request, Response};
+use futures::future::BoxFuture;
+use reqwest::{Request, Response, StatusCode};
 use reqwest_middleware::{Middleware, Next};
+use std::{
+    fmt,
+    sync::{Arc, RwLock},
+    time::{Duration, Instant},
+};
 use tracing::instrument;
 
-use crate::error::YandexWebmasterError;
+use crate::error::{Result, YandexApiErrorResponse, YandexErrorCode, YandexWebmasterError};
+
+/// Callback returning a new OAuth token
+pub(crate) type TokenRefresher = Arc<dyn Fn() -> BoxFuture<'static, Result<String>> + Send + Sync>;
+
+/// Request extension that excludes the request from OAuth authentication
+///
+/// Used for requests to other services, which must not receive the token.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Unauthenticated;
 
 /// Middleware that adds OAuth authentication to requests
-#[derive(Debug, Clone)]
+///
+/// With a [`TokenRefresher`] a request rejected with `INVALID_OAUTH_TOKEN` is retried
+/// once with a new token.
+#[derive(Clone)]
 pub struct AuthMiddleware {
-    oauth_token: String,
+    oauth_token: Arc<RwLock<String>>,
+    refresher: Option<TokenRefresher>,
+    refresh_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl fmt::Debug for AuthMiddleware {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AuthMiddleware")
+            .field("refresher", &self.refresher.is_some())
+            .finish_non_exhaustive()
+    }
 }
 
 impl AuthMiddleware {
     /// Creates a new authentication middleware with the provided OAuth token
     pub fn new(oauth_token: String) -> Self {
-        Self { oauth_token }
+        Self {
+            oauth_token: Arc::new(RwLock::new(oauth_token)),
+            refresher: None,
+            refresh_lock: Arc::new(tokio::sync::Mutex::new(())),
+        }
+    }
+
+    /// Refreshes the token with the callback on `INVALID_OAUTH_TOKEN` errors
+    pub(crate) fn with_refresher(mut self, refresher: TokenRefresher) -> Self {
+        self.refresher = Some(refresher);
+        self
+    }
+
+    fn token(&self) -> String {
+        self.oauth_token
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Replaces the token unless another request already did it since `rejected` failed
+    async fn refresh_token(&self, refresher: &TokenRefresher, rejected: &str) -> Result<()> {
+        let _guard = self.refresh_lock.lock().await;
+        if self.token() != rejected {
+            return Ok(());
+        }
+
+        let token = refresher().await?;
+        *self.oauth_token.write().unwrap_or_else(|e| e.into_inner()) = token;
+        tracing::debug!("OAuth token refreshed");
+
+        Ok(())
+    }
+}
+
+/// Sets the `Authorization` header of the request
+fn authorize(req: &mut Request, token: &str) -> reqwest_middleware::Result<()> {
+    req.headers_mut().insert(
+        reqwest::header::AUTHORIZATION,
+        reqwest::header::HeaderValue::from_str(&format!("OAuth {}", token)).map_err(|e| {
+            reqwest_middleware::Error::Middleware(anyhow::anyhow!(
+                YandexWebmasterError::MiddlewareError(format!(
+                    "Failed to create authorization header: {}",
+                    e
+                ))
+            ))
+        })?,
+    );
+
+    Ok(())
+}
+
+/// Checks whether the response is an `INVALID_OAUTH_TOKEN` error
+///
+/// Returns the response rebuilt from the consumed body along with the result.
+async fn is_invalid_token(response: Response) -> reqwest_middleware::Result<(Response, bool)> {
+    if response.status() != StatusCode::FORBIDDEN && response.status() != StatusCode::UNAUTHORIZED {
+        return Ok((response, false));
     }
+
+    let mut rebuilt = http::Response::builder().status(response.status());
+    if let Some(headers) = rebuilt.headers_mut() {
+        *headers = response.headers().clone();
+    }
+    let body = response.bytes().await?;
+    let invalid = serde_json::from_slice::<YandexApiErrorResponse>(&body)
+        .is_ok_and(|error| error.error_code == YandexErrorCode::InvalidOauthToken);
+    let response = rebuilt
+        .body(body)
+        .map_err(|e| reqwest_middleware::Error::Middleware(e.into()))?;
+
+    Ok((Response::from(response), invalid))
 }
 
 #[async_trait::async_trait]
@@ -26,20 +124,162 @@ impl Middleware for AuthMiddleware {
         extensions: &mut http::Extensions,
         next: Next<'_>,
     ) -> reqwest_middleware::Result<Response> {
-        // Add Authorization header
+        if extensions.get::<Unauthenticated>().is_some() {
+            return next.run(req, extensions).await;
+        }
+
+        let token = self.token();
+        authorize(&mut req, &token)?;
+
+        // Requests with streaming bodies can't be repeated
+        let (Some(refresher), Some(mut retry)) = (&self.refresher, req.try_clone()) else {
+            return next.run(req, extensions).await;
+        };
+
+        let (response, invalid_token) =
+            is_invalid_token(next.clone().run(req, extensions).await?).await?;
+        if !invalid_token {
+            return Ok(response);
+        }
+
+        // Retried only once, so a refresher returning a rejected token can't loop
+        self.refresh_token(refresher, &token)
+            .await
+            .map_err(|e| reqwest_middleware::Error::Middleware(anyhow::anyhow!(e)))?;
+        authorize(&mut retry, &self.token())?;
+
+        next.run(retry, extensions).await
+    }
+}
+
+/// Middleware that sets the `Accept-Language` header on requests
+#[derive(Debug, Clone)]
+pub struct LanguageMiddleware {
+    language: String,
+}
+
+impl LanguageMiddleware {
+    /// Creates a new language middleware with the provided language tag
+    pub fn new(language: String) -> Self {
+        Self { language }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for LanguageMiddleware {
+    #[instrument(skip(self, req, extensions, next))]
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut http::Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
         req.headers_mut().insert(
-            reqwest::header::AUTHORIZATION,
-            reqwest::header::HeaderValue::from_str(&format!("OAuth {}", self.oauth_token))
-                .map_err(|e| {
-                    reqwest_middleware::Error::Middleware(anyhow::anyhow!(
-                        YandexWebmasterError::MiddlewareError(format!(
-                            "Failed to create authorization header: {}",
-                            e
-                        ))
+            reqwest::header::ACCEPT_LANGUAGE,
+            reqwest::header::HeaderValue::from_str(&self.language).map_err(|e| {
+                reqwest_middleware::Error::Middleware(anyhow::anyhow!(
+                    YandexWebmasterError::MiddlewareError(format!(
+                        "Failed to create accept-language header: {}",
+                        e
                     ))
-                })?,
+                ))
+            })?,
         );
 
         next.run(req, extensions).await
     }
 }
+
+/// Middleware that retries GET requests failed to connect or timed out
+///
+/// Only connection-level `reqwest` errors are retried: responses with any status,
+/// including 429 and 5xx, are returned as is to be handled by other middleware.
+#[derive(Debug, Clone)]
+pub struct ConnectionRetryMiddleware {
+    retries: u32,
+}
+
+impl ConnectionRetryMiddleware {
+    /// Creates a new middleware retrying failed GET requests up to `retries` times
+    pub fn new(retries: u32) -> Self {
+        Self { retries }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for ConnectionRetryMiddleware {
+    async fn handle(
+        &self,
+        mut req: Request,
+        extensions: &mut http::Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        if req.method() != reqwest::Method::GET {
+            return next.run(req, extensions).await;
+        }
+
+        let mut retries_left = self.retries;
+        loop {
+            let retry = (retries_left > 0).then(|| req.try_clone()).flatten();
+            match (next.clone().run(req, extensions).await, retry) {
+                (Err(reqwest_middleware::Error::Reqwest(e)), Some(retry))
+                    if e.is_connect() || e.is_timeout() =>
+                {
+                    tracing::warn!(
+                        endpoint = %retry.url().path(),
+                        error = %e,
+                        "Connection to Yandex Webmaster API failed, retrying request"
+                    );
+                    retries_left -= 1;
+                    req = retry;
+                }
+                (result, _) => return result,
+            }
+        }
+    }
+}
+
+/// Middleware that warns about requests slower than the threshold
+///
+/// Only the method and the URL path are logged, so neither the token nor query
+/// parameters end up in the logs.
+#[derive(Debug, Clone)]
+pub struct SlowRequestMiddleware {
+    threshold: Duration,
+}
+
+impl SlowRequestMiddleware {
+    /// Creates a new middleware warning about requests slower than `threshold`
+    pub fn new(threshold: Duration) -> Self {
+        Self { threshold }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for SlowRequestMiddleware {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut http::Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let method = req.method().clone();
+        let path = req.url().path().to_string();
+        let started = Instant::now();
+
+        let result = next.run(req, extensions).await;
+
+        let elapsed = started.elapsed();
+        if elapsed > self.threshold {
+            tracing::warn!(
+                method = %method,
+                endpoint = %path,
+                elapsed_ms = elapsed.as_millis() as u64,
+                threshold_ms = self.threshold.as_millis() as u64,
+                "Slow Yandex Webmaster API request"
+            );
+        }
+
+        result
+    }
+}
@@ -0,0 +1,397 @@
+use serde::de::DeserializeOwned;
+
+use crate::error::{Result, YandexWebmasterError};
+
+/// Parses a JSON response body of the Yandex Webmaster API into one of the response types
+///
+/// The client parses every response the same way, so it can be used to check captured
+/// payloads against the crate's types when debugging deserialization failures. Failures
+/// are reported as [`YandexWebmasterError::ResponseParseError`] with the path of the
+/// failed field and `<payload>` in place of the endpoint URL. Unknown fields are
+/// ignored, as by a client without `strict_deserialization`.
+///
+/// # Example
+///
+/// ```
+/// use yandex_webmaster_api::{parse_response, UserResponse};
+///
+/// let user: UserResponse = parse_response(r#"{ "user_id": 42 }"#).unwrap();
+/// assert_eq!(user.user_id, 42);
+/// ```
+pub fn parse_response<T: DeserializeOwned>(json: &str) -> Result<T> {
+    parse_response_from(PAYLOAD_URL, json.as_bytes(), false)
+}
+
+/// URL reported in the errors of [`parse_response`], which has no endpoint to refer to
+const PAYLOAD_URL: &str = "<payload>";
+
+/// Parses a response body of the endpoint, reporting the URL and the failed field path
+///
+/// A body that ends mid-document is reported as [`YandexWebmasterError::TruncatedResponse`]
+/// to tell network failures from schema mismatches. In `strict` mode fields unknown to the
+/// response type are reported as errors too.
+pub(crate) fn parse_response_from<T: DeserializeOwned>(
+    url: &str,
+    json: &[u8],
+    strict: bool,
+) -> Result<T> {
+    let parse_error = |path: String, source: serde_json::Error| {
+        if source.is_eof() {
+            YandexWebmasterError::TruncatedResponse {
+                url: url.to_string(),
+                received: json.len(),
+                source,
+            }
+        } else {
+            YandexWebmasterError::ResponseParseError {
+                url: url.to_string(),
+                path,
+                source,
+            }
+        }
+    };
+    let deserializer = &mut serde_json::Deserializer::from_slice(json);
+
+    if !strict {
+        return serde_path_to_error::deserialize(deserializer)
+            .map_err(|e| parse_error(e.path().to_string(), e.into_inner()));
+    }
+
+    let mut unknown_field = None;
+    let mut on_unknown_field = |path: serde_ignored::Path| {
+        unknown_field.get_or_insert_with(|| path.to_string());
+    };
+    let deserializer = serde_ignored::Deserializer::new(deserializer, &mut on_unknown_field);
+    let result: T = serde_path_to_error::deserialize(deserializer)
+        .map_err(|e| parse_error(e.path().to_string(), e.into_inner()))?;
+
+    match unknown_field {
+        Some(path) => Err(parse_error(
+            path.clone(),
+            serde::de::Error::custom(format!("unknown field `{}`", path)),
+        )),
+        None => Ok(result),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dto::*;
+    use serde::Serialize;
+    use std::fmt::Debug;
+
+    /// Parses the payload, serializes it back and checks that nothing is lost
+    fn roundtrip<T: DeserializeOwned + Serialize + PartialEq + Debug>(json: &str) -> T {
+        let parsed: T = parse_response(json).unwrap();
+        let serialized = serde_json::to_string(&parsed).unwrap();
+        let reparsed: T = parse_response(&serialized).unwrap();
+        assert_eq!(parsed, reparsed);
+        parsed
+    }
+
+    #[test]
+    fn test_roundtrip_hosts() {
+        let result: HostsResponse = roundtrip(
+            r#"{
+                "hosts": [
+                    {
+                        "host_id": "http:ya.ru:80",
+                        "ascii_host_url": "http://ya.ru/",
+                        "unicode_host_url": "http://ya.ru/",
+                        "verified": true,
+                        "main_mirror": {
+                            "host_id": "https:ya.ru:443",
+                            "ascii_host_url": "https://ya.ru/",
+                            "unicode_host_url": "https://ya.ru/",
+                            "verified": true
+                        }
+                    }
+                ]
+            }"#,
+        );
+
+        assert_eq!(result.hosts.len(), 1);
+        assert!(result.hosts[0].main_mirror.is_some());
+    }
+
+    #[test]
+    fn test_roundtrip_summary() {
+        let result: HostSummaryResponse = roundtrip(
+            r#"{
+                "sqi": 10,
+                "excluded_pages_count": 1,
+                "searchable_pages_count": 5,
+                "site_problems": {
+                    "FATAL": 0,
+                    "CRITICAL": 1,
+                    "POSSIBLE_PROBLEM": 2,
+                    "RECOMMENDATION": 3
+                }
+            }"#,
+        );
+
+        assert_eq!(result.sqi, Some(10.0));
+        assert_eq!(
+            result.site_problems[&SiteProblemSeverityEnum::Recommendation],
+            3
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_diagnostics() {
+        let result: DiagnosticsResponse = roundtrip(
+            r#"{
+                "problems": {
+                    "CONNECT_FAILED": {
+                        "severity": "FATAL",
+                        "state": "PRESENT",
+                        "last_state_update": "2016-01-01T00:00:00.000+0300"
+                    },
+                    "NO_SITEMAPS": {
+                        "severity": "POSSIBLE_PROBLEM",
+                        "state": "UNDEFINED"
+                    }
+                }
+            }"#,
+        );
+
+        assert_eq!(
+            result.problems[&ApiSiteProblemTypeEnum::ConnectFailed].state,
+            ApiSiteProblemState::Present
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_popular_queries() {
+        let result: PopularQueriesResponse = roundtrip(
+            r#"{
+                "queries": [
+                    {
+                        "query_id": "c3f9a0b5",
+                        "query_text": "yandex webmaster",
+                        "indicators": {
+                            "TOTAL_SHOWS": 120.0,
+                            "TOTAL_CLICKS": 12.0
+                        }
+                    }
+                ],
+                "date_from": "2024-01-01",
+                "date_to": "2024-01-31",
+                "count": 1
+            }"#,
+        );
+
+        assert_eq!(
+            result.queries[0].indicators[&ApiQueryIndicator::TotalClicks],
+            12.0
+        );
+    }
+
+    #[test]
+    fn test_roundtrip_query_analytics() {
+        let result: QueryAnalyticsResponse = roundtrip(
+            r#"{
+                "indicators": {
+                    "TOTAL_SHOWS": [
+                        { "date": "2024-01-01T00:00:00.000+0300", "value": 100.0 },
+                        { "date": "2024-01-02T00:00:00.000+0300", "value": 110.0 }
+                    ]
+                }
+            }"#,
+        );
+
+        assert_eq!(result.indicators[&ApiQueryIndicator::TotalShows].len(), 2);
+    }
+
+    #[test]
+    fn test_roundtrip_sitemaps() {
+        let result: SitemapsResponse = roundtrip(
+            r#"{
+                "sitemaps": [
+                    {
+                        "sitemap_id": "c7-fe:80-c0",
+                        "sitemap_url": "http://example.com/sitemap.xml",
+                        "last_access_date": "2016-01-01T00:00:00.000+0300",
+                        "errors_count": 0,
+                        "urls_count": 10,
+                        "children_count": 0,
+                        "sources": ["ROBOTS_TXT", "WEBMASTER"],
+                        "sitemap_type": "SITEMAP"
+                    }
+                ]
+            }"#,
+        );
+
+        assert_eq!(result.sitemaps[0].sources.len(), 2);
+
+        let result: UserSitemapsResponse = roundtrip(
+            r#"{
+                "sitemaps": [
+                    {
+                        "sitemap_id": "c7-fe:80-c0",
+                        "sitemap_url": "http://example.com/sitemap.xml",
+                        "added_date": "2016-01-01T00:00:00.000+0300"
+                    }
+                ],
+                "count": 1
+            }"#,
+        );
+
+        assert_eq!(result.count, 1);
+    }
+
+    #[test]
+    fn test_roundtrip_search_events() {
+        let result: SearchEventsSamplesResponse = roundtrip(
+            r#"{
+                "count": 1,
+                "samples": [
+                    {
+                        "url": "http://example.com/page",
+                        "title": "Page",
+                        "event_date": "2016-01-01T00:00:00.000+0300",
+                        "last_access": "2016-01-01T00:00:00.000+0300",
+                        "event": "REMOVED_FROM_SEARCH",
+                        "excluded_url_status": "HTTP_ERROR",
+                        "bad_http_status": 404
+                    }
+                ]
+            }"#,
+        );
+
+        assert_eq!(
+            result.samples[0].excluded_url_status,
+            Some(ApiExcludedUrlStatus::HttpError)
+        );
+    }
+
+    #[test]
+    fn test_parse_empty_object() {
+        // The API returns `{}` instead of empty lists for hosts without data yet
+        let summary: HostSummaryResponse = parse_response("{}").unwrap();
+        assert_eq!(summary.sqi, None);
+        assert_eq!(summary.searchable_pages_count, 0);
+
+        let diagnostics: DiagnosticsResponse = parse_response("{}").unwrap();
+        assert!(diagnostics.problems.is_empty());
+
+        let samples: SearchUrlsSamplesResponse = parse_response("{}").unwrap();
+        assert_eq!(samples.count, 0);
+        assert!(samples.samples.is_empty());
+
+        let history: IndexingHistoryResponse = parse_response("{}").unwrap();
+        assert!(history.indicators.is_empty());
+    }
+
+    #[test]
+    fn test_parse_string_numbers() {
+        let result: SitemapsResponse = parse_response(
+            r#"{
+                "sitemaps": [
+                    {
+                        "sitemap_id": "c7-fe:80-c0",
+                        "sitemap_url": "http://example.com/sitemap.xml",
+                        "errors_count": 0,
+                        "urls_count": "10",
+                        "children_count": " 2 ",
+                        "sources": [],
+                        "sitemap_type": "SITEMAP"
+                    }
+                ]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(result.sitemaps[0].urls_count, 10);
+        assert_eq!(result.sitemaps[0].children_count, 2);
+
+        let result: SearchUrlsSamplesResponse =
+            parse_response(r#"{ "count": "42", "samples": [] }"#).unwrap();
+        assert_eq!(result.count, 42);
+
+        let error = parse_response_from::<SearchUrlsSamplesResponse>(
+            "/samples",
+            br#"{ "count": "many", "samples": [] }"#,
+            false,
+        )
+        .unwrap_err();
+        assert!(matches!(
+            error,
+            YandexWebmasterError::ResponseParseError { ref path, .. } if path == "count"
+        ));
+    }
+
+    #[test]
+    fn test_parse_response_from_error_path() {
+        let result = parse_response_from::<SitemapsResponse>(
+            "https://api.webmaster.yandex.net/v4/user/1/hosts/h/sitemaps",
+            br#"{ "sitemaps": [{ "sitemap_id": "1", "sitemap_url": "u", "errors_count": "x" }] }"#,
+            false,
+        );
+
+        match result {
+            Err(YandexWebmasterError::ResponseParseError { url, path, .. }) => {
+                assert!(url.ends_with("/sitemaps"));
+                assert_eq!(path, "sitemaps[0].errors_count");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_response_from_truncated() {
+        let json = br#"{ "sitemaps": [{ "sitemap_id": "1", "sitemap_url": "u""#;
+
+        match parse_response_from::<SitemapsResponse>("/sitemaps", json, false) {
+            Err(error @ YandexWebmasterError::TruncatedResponse { received, .. }) => {
+                assert_eq!(received, json.len());
+                assert!(error.is_retryable());
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let error =
+            parse_response_from::<SitemapsResponse>("/sitemaps", br#"{ "sitemaps": 1 }"#, false)
+                .unwrap_err();
+        assert!(!error.is_retryable());
+    }
+
+    #[test]
+    fn test_parse_response_from_invalid_utf8() {
+        let json = b"{ \"user_id\": 42, \"login\": \"\xff\" }";
+
+        let error = parse_response_from::<serde_json::Value>("/user", json, false).unwrap_err();
+        assert!(matches!(
+            error,
+            YandexWebmasterError::ResponseParseError { ref path, .. } if path == "login"
+        ));
+    }
+
+    #[test]
+    fn test_parse_response_from_strict() {
+        let json = br#"{ "user_id": 42, "login": "user" }"#;
+
+        let user: UserResponse = parse_response_from("/user", json, false).unwrap();
+        assert_eq!(user.user_id, 42);
+
+        match parse_response_from::<UserResponse>("/user", json, true) {
+            Err(YandexWebmasterError::ResponseParseError { path, source, .. }) => {
+                assert_eq!(path, "login");
+                assert!(source.to_string().contains("unknown field `login`"));
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parse_response_error() {
+        let result = parse_response::<HostsResponse>(r#"{ "hosts": [{ "host_id": 1 }] }"#);
+        match result {
+            Err(YandexWebmasterError::ResponseParseError { url, path, .. }) => {
+                assert_eq!(url, PAYLOAD_URL);
+                assert_eq!(path, "hosts[0].host_id");
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,331 @@
+use chrono::{DateTime, Utc};
+use futures::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    client::{YandexWebmasterClient, BULK_CONCURRENCY},
+    dto::{
+        DiagnosticsResponse, HostInfo, HostSummaryResponse, HostVerificationResponse, ParsedHostId,
+    },
+    error::Result,
+    snapshot::fetch_section,
+};
+
+/// Sections and hosts included by [`YandexWebmasterClient::build_account_report`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccountReportOptions {
+    /// Fetch the summary of verified sites
+    pub summary: bool,
+    /// Fetch the diagnostics of verified sites
+    pub diagnostics: bool,
+    /// Fetch the verification status of all sites
+    pub verification: bool,
+    /// Skip sites whose rights aren't verified
+    pub verified_only: bool,
+    /// Include only these sites, `None` includes all sites of the user
+    ///
+    /// IDs missing from the user's sites are reported with a `host` section error.
+    pub host_ids: Option<Vec<String>>,
+}
+
+impl Default for AccountReportOptions {
+    fn default() -> Self {
+        Self {
+            summary: true,
+            diagnostics: true,
+            verification: true,
+            verified_only: false,
+            host_ids: None,
+        }
+    }
+}
+
+/// All selected data of the user's sites from [`YandexWebmasterClient::build_account_report`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AccountReport {
+    /// ID of the user
+    pub user_id: i64,
+    /// Time the report was started
+    pub generated_at: DateTime<Utc>,
+    /// Reports of the sites in the order of the hosts list
+    pub hosts: Vec<HostReport>,
+}
+
+impl AccountReport {
+    /// Returns the reports of the sites with at least one failed section
+    pub fn failed_hosts(&self) -> impl Iterator<Item = &HostReport> {
+        self.hosts.iter().filter(|host| !host.errors.is_empty())
+    }
+}
+
+/// Data of a single site in an [`AccountReport`]
+///
+/// Sections that were disabled, failed or aren't available for unverified sites are `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostReport {
+    /// Site from the hosts list
+    pub host: HostInfo,
+    /// Site summary
+    pub summary: Option<HostSummaryResponse>,
+    /// Site diagnostics
+    pub diagnostics: Option<DiagnosticsResponse>,
+    /// Verification status
+    pub verification: Option<HostVerificationResponse>,
+    /// Sections that failed to load
+    pub errors: Vec<HostReportError>,
+}
+
+/// Section of a [`HostReport`] that failed to load
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostReportError {
+    /// Name of the section, e.g. `summary`
+    pub section: String,
+    /// Error message
+    pub message: String,
+}
+
+impl YandexWebmasterClient {
+    /// Fetch the selected data of all sites of the user as one serializable report
+    ///
+    /// Sites are processed concurrently (at most 4 at a time), and the sections of a site
+    /// are fetched concurrently too. Summary and diagnostics are only requested for
+    /// verified sites. A failed section is recorded in [`HostReport::errors`] instead of
+    /// failing the report, so only a failure to list the sites is returned as an error.
+    /// Sites of [`AccountReportOptions::host_ids`] that the user doesn't have are
+    /// appended with a `host` error and no sections.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use yandex_webmaster_api::*;
+    /// # async fn example(client: YandexWebmasterClient) -> Result<()> {
+    /// let report = client
+    ///     .build_account_report(AccountReportOptions::default())
+    ///     .await?;
+    /// println!("{}", serde_json::to_string_pretty(&report)?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn build_account_report(
+        &self,
+        options: AccountReportOptions,
+    ) -> Result<AccountReport> {
+        let generated_at = Utc::now();
+        let user_id = self.get_user_id().await?;
+
+        let all_hosts = self.get_hosts().await?;
+        let hosts: Vec<HostInfo> = all_hosts
+            .iter()
+            .filter(|host| !options.verified_only || host.verified)
+            .filter(|host| {
+                options
+                    .host_ids
+                    .as_ref()
+                    .is_none_or(|host_ids| host_ids.contains(&host.host_id))
+            })
+            .cloned()
+            .collect();
+
+        let mut hosts: Vec<HostReport> = stream::iter(hosts)
+            .map(|host| self.host_report(host, &options))
+            .buffered(BULK_CONCURRENCY)
+            .collect()
+            .await;
+
+        let missing = options
+            .host_ids
+            .iter()
+            .flatten()
+            .filter(|host_id| !all_hosts.iter().any(|host| &host.host_id == *host_id));
+        hosts.extend(missing.map(|host_id| missing_host_report(host_id)));
+
+        Ok(AccountReport {
+            user_id,
+            generated_at,
+            hosts,
+        })
+    }
+
+    /// Fetches the enabled sections of the site, collecting errors
+    async fn host_report(&self, host: HostInfo, options: &AccountReportOptions) -> HostReport {
+        let host_id = host.host_id.as_str();
+        let mut errors = Vec::new();
+
+        let (summary, diagnostics, verification) = futures::join!(
+            fetch_section(
+                options.summary && host.verified,
+                None,
+                self.get_host_summary(host_id)
+            ),
+            fetch_section(
+                options.diagnostics && host.verified,
+                None,
+                self.get_diagnostics(host_id)
+            ),
+            fetch_section(
+                options.verification,
+                None,
+                self.get_verification_status(host_id)
+            ),
+        );
+
+        let summary = record_error(&mut errors, "summary", summary);
+        let diagnostics = record_error(&mut errors, "diagnostics", diagnostics);
+        let verification = record_error(&mut errors, "verification", verification);
+
+        HostReport {
+            host,
+            summary,
+            diagnostics,
+            verification,
+            errors,
+        }
+    }
+}
+
+/// Report of a requested site that isn't among the user's sites
+fn missing_host_report(host_id: &str) -> HostReport {
+    let url = ParsedHostId::parse(host_id)
+        .map(|host| host.url())
+        .unwrap_or_default();
+    tracing::warn!(host_id = %host_id, "Account report host not found");
+
+    HostReport {
+        host: HostInfo {
+            host_id: host_id.to_string(),
+            ascii_host_url: url.clone(),
+            unicode_host_url: url,
+            verified: false,
+            main_mirror: None,
+        },
+        summary: None,
+        diagnostics: None,
+        verification: None,
+        errors: vec![HostReportError {
+            section: "host".to_string(),
+            message: format!("Host {} is not among the user's sites", host_id),
+        }],
+    }
+}
+
+/// Moves the error of a failed section to `errors`
+fn record_error<T>(
+    errors: &mut Vec<HostReportError>,
+    section: &str,
+    result: Result<Option<T>>,
+) -> Option<T> {
+    result.unwrap_or_else(|e| {
+        tracing::warn!(section = %section, error = %e, "Account report section failed");
+        errors.push(HostReportError {
+            section: section.to_string(),
+            message: e.to_string(),
+        });
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        matchers::{method, path, path_regex},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_build_account_report() {
+        let server = MockServer::start().await;
+        let host = |host_id: &str, verified: bool| HostInfo {
+            host_id: host_id.to_string(),
+            verified,
+            ..HostInfo::sample()
+        };
+        Mock::given(method("GET"))
+            .and(path("/user/1/hosts"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "hosts": [
+                    host("https:ok.com:443", true),
+                    host("https:failing.com:443", true),
+                    host("https:new.com:443", false)
+                ]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/hosts/https:failing.com:443/summary$"))
+            .respond_with(ResponseTemplate::new(500).set_body_json(serde_json::json!({
+                "error_code": "INTERNAL_ERROR",
+                "error_message": "some string"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/summary$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostSummaryResponse::sample()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/diagnostics$"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "problems": {} })),
+            )
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let report = client
+            .build_account_report(AccountReportOptions {
+                verification: false,
+                ..AccountReportOptions::default()
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(report.user_id, 1);
+        let hosts: Vec<_> = report
+            .hosts
+            .iter()
+            .map(|h| (h.host.host_id.as_str(), h.summary.is_some(), h.errors.len()))
+            .collect();
+        assert_eq!(
+            hosts,
+            [
+                ("https:ok.com:443", true, 0),
+                ("https:failing.com:443", false, 1),
+                ("https:new.com:443", false, 0)
+            ]
+        );
+
+        let failed: Vec<_> = report.failed_hosts().collect();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].errors[0].section, "summary");
+        assert!(serde_json::to_value(&report).is_ok());
+
+        let report = client
+            .build_account_report(AccountReportOptions {
+                summary: false,
+                diagnostics: false,
+                verification: false,
+                verified_only: false,
+                host_ids: Some(vec![
+                    "https:ok.com:443".to_string(),
+                    "https:gone.com:443".to_string(),
+                ]),
+            })
+            .await
+            .unwrap();
+
+        let hosts: Vec<_> = report
+            .hosts
+            .iter()
+            .map(|h| (h.host.host_id.as_str(), h.errors.len()))
+            .collect();
+        assert_eq!(hosts, [("https:ok.com:443", 0), ("https:gone.com:443", 1)]);
+        assert_eq!(report.hosts[1].errors[0].section, "host");
+        assert_eq!(report.hosts[1].host.ascii_host_url, "https://gone.com/");
+    }
+}
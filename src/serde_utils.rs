@@ -0,0 +1,121 @@
+//! Custom (de)serialization of API fields
+
+use serde::{Deserialize, Deserializer};
+use std::{fmt, str::FromStr};
+
+/// Encoding of query parameters used by the client (arrays as repeated keys)
+pub(crate) fn query_config() -> serde_qs::Config {
+    serde_qs::Config::new().array_format(serde_qs::ArrayFormat::Unindexed)
+}
+
+/// Format of dates in query parameters of the API (`YYYY-MM-DD`)
+pub(crate) const QUERY_DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Optional [`chrono::NaiveDate`] in the [`QUERY_DATE_FORMAT`]
+///
+/// Use with `#[serde(default, with = "crate::serde_utils::option_query_date")]`.
+pub(crate) mod option_query_date {
+    use chrono::NaiveDate;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::QUERY_DATE_FORMAT;
+
+    pub(crate) fn serialize<S: Serializer>(
+        date: &Option<NaiveDate>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => serializer.collect_str(&date.format(QUERY_DATE_FORMAT)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<NaiveDate>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| NaiveDate::parse_from_str(&s, QUERY_DATE_FORMAT))
+            .transpose()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Format of date-times in query parameters of the API, in UTC with whole seconds
+pub(crate) const QUERY_DATE_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Optional [`chrono::DateTime<Utc>`] in the [`QUERY_DATE_TIME_FORMAT`]
+///
+/// Fractions of seconds are dropped. Any RFC 3339 date-time is accepted when
+/// deserializing. Use with
+/// `#[serde(default, with = "crate::serde_utils::option_query_date_time")]`.
+pub(crate) mod option_query_date_time {
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::QUERY_DATE_TIME_FORMAT;
+
+    pub(crate) fn serialize<S: Serializer>(
+        date: &Option<DateTime<Utc>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match date {
+            Some(date) => serializer.collect_str(&date.format(QUERY_DATE_TIME_FORMAT)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<DateTime<Utc>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| DateTime::parse_from_rfc3339(&s).map(|date| date.with_timezone(&Utc)))
+            .transpose()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Number that the API may also send as a string, e.g. `42` or `"42"`
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum StringOrNumber<T> {
+    Number(T),
+    String(String),
+}
+
+impl<T: FromStr> StringOrNumber<T>
+where
+    T::Err: fmt::Display,
+{
+    fn into_number<E: serde::de::Error>(self) -> Result<T, E> {
+        match self {
+            StringOrNumber::Number(number) => Ok(number),
+            StringOrNumber::String(s) => s.trim().parse().map_err(E::custom),
+        }
+    }
+}
+
+/// Deserializes a number from a JSON number or a string containing one
+///
+/// Use with `#[serde(deserialize_with = "crate::serde_utils::string_or_number")]`.
+pub(crate) fn string_or_number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: fmt::Display,
+{
+    StringOrNumber::deserialize(deserializer)?.into_number()
+}
+
+/// Optional variant of [`string_or_number`], `null` is `None`
+///
+/// Use with `#[serde(default, deserialize_with = "crate::serde_utils::option_string_or_number")]`.
+pub(crate) fn option_string_or_number<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + FromStr,
+    T::Err: fmt::Display,
+{
+    Option::<StringOrNumber<T>>::deserialize(deserializer)?
+        .map(StringOrNumber::into_number)
+        .transpose()
+}
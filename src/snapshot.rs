@@ -0,0 +1,228 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{future::Future, time::Duration};
+use tracing::instrument;
+
+use crate::{
+    client::YandexWebmasterClient,
+    dto::{
+        DiagnosticsResponse, HostSummaryResponse, ImportantUrl, IndexingHistoryRequest,
+        IndexingHistoryResponse, SitemapInfo, SqiHistoryRequest, SqiPoint,
+    },
+    error::Result,
+};
+
+/// Sections fetched by [`YandexWebmasterClient::snapshot_host`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SnapshotOptions {
+    /// Fetch the site summary
+    pub summary: bool,
+    /// Fetch the site diagnostics
+    pub diagnostics: bool,
+    /// Fetch all top-level sitemaps
+    pub sitemaps: bool,
+    /// Fetch the important URLs
+    pub important_urls: bool,
+    /// Fetch the indexing history for the last [`Self::history_days`]
+    pub indexing_history: bool,
+    /// Fetch the SQI history for the last [`Self::history_days`]
+    pub sqi_history: bool,
+    /// Number of days of the indexing and SQI history
+    pub history_days: i64,
+    /// Drops sections that take longer, `None` waits for all sections
+    pub section_timeout: Option<Duration>,
+}
+
+impl Default for SnapshotOptions {
+    fn default() -> Self {
+        Self {
+            summary: true,
+            diagnostics: true,
+            sitemaps: true,
+            important_urls: true,
+            indexing_history: true,
+            sqi_history: true,
+            history_days: 30,
+            section_timeout: None,
+        }
+    }
+}
+
+/// Data of a site at a point in time from [`YandexWebmasterClient::snapshot_host`]
+///
+/// Sections that were disabled or [timed out](Self::timed_out) are `None`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HostSnapshot {
+    /// Host ID
+    pub host_id: String,
+    /// Time the snapshot was started
+    pub taken_at: DateTime<Utc>,
+    /// Site summary
+    pub summary: Option<HostSummaryResponse>,
+    /// Site diagnostics
+    pub diagnostics: Option<DiagnosticsResponse>,
+    /// Top-level sitemaps
+    pub sitemaps: Option<Vec<SitemapInfo>>,
+    /// Important URLs
+    pub important_urls: Option<Vec<ImportantUrl>>,
+    /// Indexing history
+    pub indexing_history: Option<IndexingHistoryResponse>,
+    /// SQI history
+    pub sqi_history: Option<Vec<SqiPoint>>,
+    /// Names of the enabled sections dropped because of the section timeout
+    pub timed_out: Vec<String>,
+}
+
+impl YandexWebmasterClient {
+    /// Fetch the selected data of a site concurrently for archival
+    ///
+    /// Fails if any enabled section fails. A section that takes longer than
+    /// [`SnapshotOptions::section_timeout`] is left empty and listed in
+    /// [`HostSnapshot::timed_out`]. Dropping the returned future cancels all sections.
+    #[instrument(skip(self), parent = self.span_parent())]
+    pub async fn snapshot_host(
+        &self,
+        host_id: &str,
+        options: SnapshotOptions,
+    ) -> Result<HostSnapshot> {
+        let taken_at = Utc::now();
+        let timeout = options.section_timeout;
+        let indexing_request = IndexingHistoryRequest::last_days(options.history_days);
+        let sqi_request = SqiHistoryRequest::last_days(options.history_days);
+
+        let (summary, diagnostics, sitemaps, important_urls, indexing_history, sqi_history) = futures::try_join!(
+            fetch_section(options.summary, timeout, self.get_host_summary(host_id)),
+            fetch_section(options.diagnostics, timeout, self.get_diagnostics(host_id)),
+            fetch_section(options.sitemaps, timeout, self.get_all_sitemaps(host_id)),
+            fetch_section(options.important_urls, timeout, async {
+                Ok(self.get_important_urls(host_id).await?.urls)
+            }),
+            fetch_section(
+                options.indexing_history,
+                timeout,
+                self.get_indexing_history(host_id, &indexing_request),
+            ),
+            fetch_section(
+                options.sqi_history,
+                timeout,
+                self.get_sqi_history(host_id, sqi_request),
+            ),
+        )?;
+
+        let timed_out = [
+            ("summary", options.summary && summary.is_none()),
+            ("diagnostics", options.diagnostics && diagnostics.is_none()),
+            ("sitemaps", options.sitemaps && sitemaps.is_none()),
+            (
+                "important_urls",
+                options.important_urls && important_urls.is_none(),
+            ),
+            (
+                "indexing_history",
+                options.indexing_history && indexing_history.is_none(),
+            ),
+            ("sqi_history", options.sqi_history && sqi_history.is_none()),
+        ]
+        .into_iter()
+        .filter(|(_, timed_out)| *timed_out)
+        .map(|(section, _)| section.to_string())
+        .collect();
+
+        Ok(HostSnapshot {
+            host_id: host_id.to_string(),
+            taken_at,
+            summary,
+            diagnostics,
+            sitemaps,
+            important_urls,
+            indexing_history,
+            sqi_history,
+            timed_out,
+        })
+    }
+}
+
+/// Fetches an enabled section, `None` if it is disabled or exceeds the timeout
+pub(crate) async fn fetch_section<T>(
+    enabled: bool,
+    timeout: Option<Duration>,
+    fetch: impl Future<Output = Result<T>>,
+) -> Result<Option<T>> {
+    if !enabled {
+        return Ok(None);
+    }
+
+    match timeout {
+        Some(timeout) => match tokio::time::timeout(timeout, fetch).await {
+            Ok(result) => result.map(Some),
+            Err(_) => {
+                tracing::warn!(timeout_ms = timeout.as_millis() as u64, "Section timed out");
+                Ok(None)
+            }
+        },
+        None => fetch.await.map(Some),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::SAMPLE_HOST_ID;
+    use wiremock::{
+        matchers::{method, path_regex},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_snapshot_host() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path_regex("/summary$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(HostSummaryResponse::sample()))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/diagnostics$"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "problems": {} }))
+                    .set_delay(Duration::from_secs(5)),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/sitemaps$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "sitemaps": [SitemapInfo::sample()]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path_regex("/sqi-history$"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "points": [{ "date": "2024-01-01T00:00:00.000+0300", "value": 10.0 }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        let options = SnapshotOptions {
+            important_urls: false,
+            indexing_history: false,
+            section_timeout: Some(Duration::from_millis(200)),
+            ..SnapshotOptions::default()
+        };
+        let snapshot = client.snapshot_host(SAMPLE_HOST_ID, options).await.unwrap();
+
+        assert_eq!(snapshot.host_id, SAMPLE_HOST_ID);
+        assert_eq!(snapshot.summary, Some(HostSummaryResponse::sample()));
+        assert_eq!(snapshot.diagnostics, None);
+        assert_eq!(snapshot.sitemaps, Some(vec![SitemapInfo::sample()]));
+        assert_eq!(snapshot.important_urls, None);
+        assert_eq!(snapshot.sqi_history.map(|points| points.len()), Some(1));
+        assert_eq!(snapshot.timed_out, ["diagnostics"]);
+    }
+}
@@ -0,0 +1,301 @@
+//! Helpers for testing code that uses the client
+//!
+//! Available with the `test-util` feature. Combine [`YandexWebmasterClient::mock`] with a
+//! mock HTTP server (e.g. `wiremock`) and the `sample()` constructors of the response types.
+
+use chrono::{TimeZone, Utc};
+use reqwest::{Request, Response};
+use reqwest_middleware::{ClientBuilder, Middleware, Next};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use crate::{client::YandexWebmasterClient, dto::*};
+
+/// User ID used by [`YandexWebmasterClient::mock`]
+pub const MOCK_USER_ID: i64 = 1;
+
+/// OAuth token sent by [`YandexWebmasterClient::mock`]
+pub const MOCK_OAUTH_TOKEN: &str = "test-token";
+
+/// Host ID used by the `sample()` constructors
+pub const SAMPLE_HOST_ID: &str = "https:example.com:443";
+
+impl YandexWebmasterClient {
+    /// Creates a client sending requests to `base_url` without fetching the user
+    ///
+    /// The user ID is always [`MOCK_USER_ID`] and the `Authorization` header carries
+    /// [`MOCK_OAUTH_TOKEN`], so request paths look like `/user/1/hosts/...`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be created.
+    pub fn mock(base_url: String) -> Self {
+        let mut builder = Self::builder(MOCK_OAUTH_TOKEN.to_string()).base_url(base_url);
+        let client = Self::build_client(&mut builder).expect("failed to create HTTP client");
+        Self::from_parts(builder, client, Some(MOCK_USER_ID))
+    }
+
+    /// Creates a [mock](Self::mock) client that records every outgoing request
+    ///
+    /// # Panics
+    ///
+    /// Panics if the HTTP client cannot be created.
+    pub fn mock_recording(base_url: String) -> (Self, RequestRecorder) {
+        let recorder = RequestRecorder::default();
+        let mut builder = Self::builder(MOCK_OAUTH_TOKEN.to_string())
+            .base_url(base_url)
+            .client(ClientBuilder::new(reqwest::Client::new()).with(recorder.clone()));
+        let client = Self::build_client(&mut builder).expect("failed to create HTTP client");
+
+        (
+            Self::from_parts(builder, client, Some(MOCK_USER_ID)),
+            recorder,
+        )
+    }
+}
+
+/// Request captured by [`RequestRecorder`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    /// HTTP method
+    pub method: reqwest::Method,
+    /// Full URL including the query string
+    pub url: reqwest::Url,
+    /// Request body as sent (compressed bodies are not decompressed)
+    pub body: Option<Vec<u8>>,
+}
+
+impl RecordedRequest {
+    /// Returns the value of the query parameter
+    pub fn query_param(&self, name: &str) -> Option<String> {
+        self.url
+            .query_pairs()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.into_owned())
+    }
+
+    /// Parses the body as JSON
+    pub fn body_json(&self) -> Option<serde_json::Value> {
+        self.body
+            .as_deref()
+            .and_then(|body| serde_json::from_slice(body).ok())
+    }
+}
+
+/// Middleware that stores every outgoing request in a shared buffer
+///
+/// Clones share the buffer, so the recorder can be inspected after passing it to a
+/// client builder or getting it from [`YandexWebmasterClient::mock_recording`].
+#[derive(Debug, Clone, Default)]
+pub struct RequestRecorder {
+    requests: Arc<Mutex<Vec<RecordedRequest>>>,
+}
+
+impl RequestRecorder {
+    /// Returns the requests recorded so far
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Returns the last recorded request
+    pub fn last(&self) -> Option<RecordedRequest> {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .last()
+            .cloned()
+    }
+
+    /// Removes all recorded requests
+    pub fn clear(&self) {
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clear();
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for RequestRecorder {
+    async fn handle(
+        &self,
+        req: Request,
+        extensions: &mut http::Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<Response> {
+        let recorded = RecordedRequest {
+            method: req.method().clone(),
+            url: req.url().clone(),
+            body: req.body().and_then(|b| b.as_bytes()).map(<[u8]>::to_vec),
+        };
+        self.requests
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .push(recorded);
+
+        next.run(req, extensions).await
+    }
+}
+
+impl HostInfo {
+    /// Sample verified host `https://example.com/`
+    pub fn sample() -> Self {
+        Self {
+            host_id: SAMPLE_HOST_ID.to_string(),
+            ascii_host_url: "https://example.com/".to_string(),
+            unicode_host_url: "https://example.com/".to_string(),
+            verified: true,
+            main_mirror: None,
+        }
+    }
+}
+
+impl FullHostInfo {
+    /// Sample verified and indexed host `https://example.com/`
+    pub fn sample() -> Self {
+        let host = HostInfo::sample();
+        Self {
+            host_id: host.host_id,
+            ascii_host_url: host.ascii_host_url,
+            unicode_host_url: host.unicode_host_url,
+            verified: host.verified,
+            main_mirror: None,
+            host_data_status: Some(HostDataStatus::Ok),
+            host_display_name: Some("Example".to_string()),
+        }
+    }
+}
+
+impl HostSummaryResponse {
+    /// Sample summary of an indexed site without problems
+    pub fn sample() -> Self {
+        Self {
+            sqi: Some(10.0),
+            searchable_pages_count: 100,
+            excluded_pages_count: 10,
+            site_problems: HashMap::new(),
+        }
+    }
+}
+
+impl SitemapInfo {
+    /// Sample sitemap `https://example.com/sitemap.xml` added by the user
+    pub fn sample() -> Self {
+        Self {
+            sitemap_id: "c7-fe:80-c0".to_string(),
+            sitemap_url: "https://example.com/sitemap.xml".to_string(),
+            last_access_date: Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+            errors_count: 0,
+            urls_count: 100,
+            children_count: 0,
+            sources: vec![ApiSitemapSource::Webmaster],
+            sitemap_type: ApiSitemapType::Sitemap,
+        }
+    }
+}
+
+impl UserSitemapInfo {
+    /// Sample user-added sitemap `https://example.com/sitemap.xml`
+    pub fn sample() -> Self {
+        Self {
+            sitemap_id: "c7-fe:80-c0".to_string(),
+            sitemap_url: "https://example.com/sitemap.xml".to_string(),
+            added_date: Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap(),
+        }
+    }
+}
+
+impl RecrawlTask {
+    /// Sample finished recrawl task for `https://example.com/`
+    pub fn sample() -> Self {
+        Self {
+            task_id: "ff4a3f76-1e23-4c5a-a9a5-8b1ea1c4e9d1".to_string(),
+            url: "https://example.com/".to_string(),
+            added_time: Some(Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap()),
+            state: RecrawlTaskState::Done,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::{
+        matchers::{header, method, path},
+        Mock, MockServer, ResponseTemplate,
+    };
+
+    #[tokio::test]
+    async fn test_mock_client() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path(format!(
+                "/user/{}/hosts/{}",
+                MOCK_USER_ID, SAMPLE_HOST_ID
+            )))
+            .and(header("Authorization", "OAuth test-token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(FullHostInfo::sample()))
+            .mount(&server)
+            .await;
+
+        let client = YandexWebmasterClient::mock(server.uri());
+        assert_eq!(client.user_id(), MOCK_USER_ID);
+
+        let host = client.get_host(SAMPLE_HOST_ID).await.unwrap();
+        assert_eq!(host, FullHostInfo::sample());
+    }
+
+    #[tokio::test]
+    async fn test_request_recorder() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "queries": [],
+                "date_from": "2024-01-01",
+                "date_to": "2024-01-31",
+                "count": 0
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .respond_with(
+                ResponseTemplate::new(202).set_body_json(serde_json::json!({ "task_id": "1" })),
+            )
+            .mount(&server)
+            .await;
+
+        let (client, recorder) = YandexWebmasterClient::mock_recording(server.uri());
+
+        let request = PopularQueriesRequest::builder()
+            .order_by(ApiQueryOrderField::TotalShows)
+            .build();
+        client
+            .get_popular_queries(SAMPLE_HOST_ID, &request)
+            .await
+            .unwrap();
+        client
+            .recrawl_urls(SAMPLE_HOST_ID, "https://example.com/")
+            .await
+            .unwrap();
+
+        let requests = recorder.requests();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].method, reqwest::Method::GET);
+        assert_eq!(
+            requests[0].query_param("order_by").as_deref(),
+            Some("TOTAL_SHOWS")
+        );
+        assert_eq!(
+            requests[1].body_json(),
+            Some(serde_json::json!({ "url": "https://example.com/" }))
+        );
+
+        recorder.clear();
+        assert!(recorder.last().is_none());
+    }
+}
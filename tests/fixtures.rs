@@ -0,0 +1,78 @@
+//! Contract tests checking that API responses in `tests/fixtures` deserialize into the DTOs
+//!
+//! Fixtures follow the examples of the API documentation. A fixture field unknown to the
+//! target type fails the test too, so renaming a DTO field without updating its
+//! `serde` attributes is caught here.
+
+use serde::de::DeserializeOwned;
+use yandex_webmaster_api::*;
+
+/// Parses the fixture, failing on errors and fields the type doesn't know
+fn parse_fixture<T: DeserializeOwned>(name: &str, json: &str) -> T {
+    let mut unknown_fields = Vec::new();
+    let deserializer = &mut serde_json::Deserializer::from_str(json);
+    let value =
+        serde_ignored::deserialize(deserializer, |path| unknown_fields.push(path.to_string()))
+            .unwrap_or_else(|e| panic!("fixture {name} doesn't match the type: {e}"));
+
+    assert!(
+        unknown_fields.is_empty(),
+        "fixture {name} has fields unknown to the type: {unknown_fields:?}"
+    );
+    value
+}
+
+macro_rules! fixture_tests {
+    ($($name:ident: $type:ty),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                parse_fixture::<$type>(
+                    stringify!($name),
+                    include_str!(concat!("fixtures/", stringify!($name), ".json")),
+                );
+            }
+        )*
+    };
+}
+
+fixture_tests! {
+    user: UserResponse,
+    hosts: HostsResponse,
+    full_host_info: FullHostInfo,
+    add_host: AddHostResponse,
+    host_verification_status: HostVerificationStatusResponse,
+    host_verification: HostVerificationResponse,
+    owners: OwnersResponse,
+    host_summary: HostSummaryResponse,
+    excluded_pages_statistics: ExcludedPagesStatistics,
+    sqi_history: SqiHistoryResponse,
+    popular_queries: PopularQueriesResponse,
+    query_analytics: QueryAnalyticsResponse,
+    query_history: QueryHistoryResponse,
+    sitemaps: SitemapsResponse,
+    sitemap_info: SitemapInfo,
+    user_sitemaps: UserSitemapsResponse,
+    user_sitemap_info: UserSitemapInfo,
+    add_sitemap: AddSitemapResponse,
+    indexing_history: IndexingHistoryResponse,
+    indexing_samples: IndexingSamplesResponse,
+    search_urls_history: SearchUrlsHistoryResponse,
+    search_urls_samples: SearchUrlsSamplesResponse,
+    search_events_history: SearchEventsHistoryResponse,
+    search_events_samples: SearchEventsSamplesResponse,
+    recrawl: RecrawlResponse,
+    recrawl_tasks: RecrawlTasksResponse,
+    recrawl_task: RecrawlTask,
+    recrawl_quota: RecrawlQuotaResponse,
+    broken_links: BrokenLinksResponse,
+    broken_link_history: BrokenLinkHistoryResponse,
+    external_links: ExternalLinksResponse,
+    external_links_history: ExternalLinksHistoryResponse,
+    diagnostics: DiagnosticsResponse,
+    feeds: FeedsResponse,
+    add_feed: AddFeedResponse,
+    feed_add_info: FeedAddInfo,
+    important_urls: ImportantUrlsResponse,
+    important_url_history: ImportantUrlHistoryResponse,
+}
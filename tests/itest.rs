@@ -93,12 +93,7 @@ async fn should_create_and_verify_host() -> anyhow::Result<()> {
 async fn get_host_owners() -> anyhow::Result<()> {
     let client = new_client().await?;
 
-    let host = client
-        .get_hosts()
-        .await?
-        .into_iter()
-        .find(|s| s.verified)
-        .unwrap();
+    let host = client.get_primary_verified_host().await?;
 
     let owners = client.get_owners(&host.host_id).await?;
 
@@ -114,12 +109,7 @@ async fn get_host_owners() -> anyhow::Result<()> {
 async fn get_sqi() -> anyhow::Result<()> {
     let client = new_client().await?;
 
-    let host = client
-        .get_hosts()
-        .await?
-        .into_iter()
-        .find(|s| s.verified)
-        .unwrap();
+    let host = client.get_primary_verified_host().await?;
 
     let history = client
         .get_sqi_history(
@@ -147,12 +137,7 @@ async fn get_sqi() -> anyhow::Result<()> {
 async fn get_search_queries() -> anyhow::Result<()> {
     let client = new_client().await?;
 
-    let host = client
-        .get_hosts()
-        .await?
-        .into_iter()
-        .find(|s| s.verified)
-        .unwrap();
+    let host = client.get_primary_verified_host().await?;
 
     dbg!(&host);
 
@@ -178,7 +163,7 @@ async fn get_search_queries() -> anyhow::Result<()> {
                     ApiQueryIndicator::TotalShows,
                 ])
                 .date_from(Utc::now() - Duration::days(90))
-                .build(),
+                .build()?,
         )
         .await?;
 
@@ -202,7 +187,7 @@ async fn get_search_queries() -> anyhow::Result<()> {
                     ApiQueryIndicator::TotalClicks,
                     ApiQueryIndicator::TotalShows,
                 ])
-                .build(),
+                .build()?,
         )
         .await?;
 
@@ -216,12 +201,7 @@ async fn get_search_queries() -> anyhow::Result<()> {
 async fn work_with_sitemaps() -> anyhow::Result<()> {
     let client = new_client().await?;
 
-    let host = client
-        .get_hosts()
-        .await?
-        .into_iter()
-        .find(|s| s.verified)
-        .unwrap();
+    let host = client.get_primary_verified_host().await?;
 
     dbg!(&host);
 
@@ -273,12 +253,7 @@ async fn work_with_sitemaps() -> anyhow::Result<()> {
 async fn get_indexing() -> anyhow::Result<()> {
     let client = new_client().await?;
 
-    let host = client
-        .get_hosts()
-        .await?
-        .into_iter()
-        .find(|s| s.verified)
-        .unwrap();
+    let host = client.get_primary_verified_host().await?;
 
     let stats = client.get_host_summary(&host.host_id).await?;
 
@@ -316,12 +291,7 @@ async fn get_indexing() -> anyhow::Result<()> {
 async fn search_methods() -> anyhow::Result<()> {
     let client = new_client().await?;
 
-    let host = client
-        .get_hosts()
-        .await?
-        .into_iter()
-        .find(|s| s.verified)
-        .unwrap();
+    let host = client.get_primary_verified_host().await?;
 
     let history = client
         .get_search_urls_history(&host.host_id, &IndexingHistoryRequest::default())
@@ -355,12 +325,7 @@ async fn search_methods() -> anyhow::Result<()> {
 async fn reindex() -> anyhow::Result<()> {
     let client = new_client().await?;
 
-    let host = client
-        .get_hosts()
-        .await?
-        .into_iter()
-        .find(|s| s.verified)
-        .unwrap();
+    let host = client.get_primary_verified_host().await?;
 
     let task = client
         .recrawl_urls(&host.host_id, &"https://seomeow.com")
@@ -392,12 +357,7 @@ async fn reindex() -> anyhow::Result<()> {
 async fn site_diagnostics() -> anyhow::Result<()> {
     let client = new_client().await?;
 
-    let host = client
-        .get_hosts()
-        .await?
-        .into_iter()
-        .find(|s| s.verified)
-        .unwrap();
+    let host = client.get_primary_verified_host().await?;
 
     let diagnostics = client.get_diagnostics(&host.host_id).await?;
 
@@ -411,12 +371,7 @@ async fn site_diagnostics() -> anyhow::Result<()> {
 async fn links() -> anyhow::Result<()> {
     let client = new_client().await?;
 
-    let host = client
-        .get_hosts()
-        .await?
-        .into_iter()
-        .find(|s| s.verified)
-        .unwrap();
+    let host = client.get_primary_verified_host().await?;
 
     let links = client
         .get_broken_links(&host.host_id, &BrokenLinksRequest::default())